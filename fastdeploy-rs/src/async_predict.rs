@@ -0,0 +1,63 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use crate::errors::FastDeployError;
+use crate::pool::ModelPool;
+use crate::predictor::Predictor;
+use crate::type_bridge::Mat;
+
+/// Runs inference on tokio's blocking thread pool so async handlers don't stall the executor.
+///
+/// Dropping the returned future doesn't abort a prediction that already started. It finishes on
+/// the blocking thread and the model is released afterwards, so a cancelled request never leaves
+/// a model checked out or locked.
+///
+/// Implemented for pooled or locked `Predictor`s, which every model wrapper is except the OCR
+/// sub models `DBDetector`, `Classifier` and `StructureV2Table`. Their outputs are plain tuples
+/// and vectors, and they are meant to be run through `PPOCRv2`/`PPOCRv3`/`PPStructureV2Table`.
+pub trait AsyncPredictor {
+    type Output: Send + 'static;
+
+    fn predict_async(&self, img: Mat) -> impl Future<Output=Result<Self::Output, FastDeployError>> + Send;
+
+    fn batch_predict_async(&self, imgs: Vec<Mat>) -> impl Future<Output=Result<Vec<Self::Output>, FastDeployError>> + Send;
+}
+
+async fn run_blocking<T, F>(f: F) -> Result<T, FastDeployError>
+    where T: Send + 'static,
+          F: FnOnce() -> Result<T, FastDeployError> + Send + 'static {
+    // a join error means the prediction panicked
+    tokio::task::spawn_blocking(f).await.unwrap_or(Err(FastDeployError::PredictError))
+}
+
+impl<M> AsyncPredictor for Arc<ModelPool<M>>
+    where M: Predictor + Send + 'static,
+          M::Output: Send + 'static {
+    type Output = M::Output;
+
+    fn predict_async(&self, img: Mat) -> impl Future<Output=Result<M::Output, FastDeployError>> + Send {
+        let pool = self.clone();
+        run_blocking(move || pool.get().predict(&img))
+    }
+
+    fn batch_predict_async(&self, imgs: Vec<Mat>) -> impl Future<Output=Result<Vec<M::Output>, FastDeployError>> + Send {
+        let pool = self.clone();
+        run_blocking(move || pool.get().batch_predict(&imgs.iter().collect::<Vec<_>>()))
+    }
+}
+
+impl<M> AsyncPredictor for Arc<Mutex<M>>
+    where M: Predictor + Send + 'static,
+          M::Output: Send + 'static {
+    type Output = M::Output;
+
+    fn predict_async(&self, img: Mat) -> impl Future<Output=Result<M::Output, FastDeployError>> + Send {
+        let model = self.clone();
+        run_blocking(move || model.lock().unwrap_or_else(|e| e.into_inner()).predict(&img))
+    }
+
+    fn batch_predict_async(&self, imgs: Vec<Mat>) -> impl Future<Output=Result<Vec<M::Output>, FastDeployError>> + Send {
+        let model = self.clone();
+        run_blocking(move || model.lock().unwrap_or_else(|e| e.into_inner()).batch_predict(&imgs.iter().collect::<Vec<_>>()))
+    }
+}
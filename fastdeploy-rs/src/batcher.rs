@@ -0,0 +1,140 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::errors::FastDeployError;
+use crate::predictor::Predictor;
+use crate::type_bridge::Mat;
+
+#[derive(Debug, Copy, Clone)]
+pub struct BatcherConfig {
+    pub max_batch_size: usize,
+    /// How long the first request of a batch waits for others to join it.
+    pub max_wait: Duration,
+}
+
+impl Default for BatcherConfig {
+    fn default() -> Self {
+        BatcherConfig { max_batch_size: 8, max_wait: Duration::from_millis(5) }
+    }
+}
+
+struct Request<O> {
+    img: Mat,
+    deadline: Option<Instant>,
+    reply: Sender<Result<O, FastDeployError>>,
+}
+
+/// Collects single image requests from many threads into `batch_predict` calls on one model.
+pub struct Batcher<O> {
+    sender: Option<Sender<Request<O>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<O: Send + 'static> Batcher<O> {
+    pub fn new<M>(model: M, config: BatcherConfig) -> Batcher<O>
+        where M: Predictor<Output=O> + Send + 'static {
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || run_worker(model, config, receiver));
+        Batcher { sender: Some(sender), worker: Some(worker) }
+    }
+
+    pub fn predict(&self, img: Mat) -> Result<O, FastDeployError> {
+        self.submit(img, None)
+    }
+
+    /// Fails with `DeadlineExceeded` if the result isn't ready by `deadline`. A request that is
+    /// still queued at its deadline is dropped without running the model.
+    pub fn predict_with_deadline(&self, img: Mat, deadline: Instant) -> Result<O, FastDeployError> {
+        self.submit(img, Some(deadline))
+    }
+
+    fn submit(&self, img: Mat, deadline: Option<Instant>) -> Result<O, FastDeployError> {
+        let (reply, result) = mpsc::channel();
+        let sender = self.sender.as_ref().ok_or(FastDeployError::PredictError)?;
+        sender.send(Request { img, deadline, reply }).map_err(|_| FastDeployError::PredictError)?;
+        match deadline {
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match result.recv_timeout(timeout) {
+                    Ok(result) => result,
+                    Err(RecvTimeoutError::Timeout) => Err(FastDeployError::DeadlineExceeded),
+                    Err(RecvTimeoutError::Disconnected) => Err(FastDeployError::PredictError),
+                }
+            }
+            None => result.recv().unwrap_or(Err(FastDeployError::PredictError)),
+        }
+    }
+}
+
+impl<O> Drop for Batcher<O> {
+    fn drop(&mut self) {
+        // closing the channel lets the worker finish the queued requests and exit
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run_worker<M: Predictor>(model: M, config: BatcherConfig, receiver: Receiver<Request<M::Output>>) {
+    let max_batch_size = config.max_batch_size.max(1);
+    while let Ok(first) = receiver.recv() {
+        let mut flush_at = Instant::now() + config.max_wait;
+        if let Some(deadline) = first.deadline {
+            flush_at = flush_at.min(deadline);
+        }
+        let mut batch = vec![first];
+        while batch.len() < max_batch_size {
+            let timeout = flush_at.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                break;
+            }
+            match receiver.recv_timeout(timeout) {
+                Ok(request) => {
+                    if let Some(deadline) = request.deadline {
+                        flush_at = flush_at.min(deadline);
+                    }
+                    batch.push(request);
+                }
+                Err(_) => break,
+            }
+        }
+        run_batch(&model, batch);
+    }
+}
+
+fn run_batch<M: Predictor>(model: &M, batch: Vec<Request<M::Output>>) {
+    let now = Instant::now();
+    let mut imgs = Vec::with_capacity(batch.len());
+    let mut replies = Vec::with_capacity(batch.len());
+    for request in batch {
+        if request.deadline.is_some_and(|deadline| deadline <= now) {
+            let _ = request.reply.send(Err(FastDeployError::DeadlineExceeded));
+            continue;
+        }
+        imgs.push(request.img);
+        replies.push(request.reply);
+    }
+    if imgs.is_empty() {
+        return;
+    }
+    let img_refs: Vec<&Mat> = imgs.iter().collect();
+    match model.batch_predict(&img_refs) {
+        Ok(results) if results.len() == replies.len() => {
+            for (reply, result) in replies.into_iter().zip(results) {
+                let _ = reply.send(Ok(result));
+            }
+        }
+        Ok(_) => {
+            for reply in replies {
+                let _ = reply.send(Err(FastDeployError::PredictError));
+            }
+        }
+        Err(e) => {
+            for reply in replies {
+                let _ = reply.send(Err(e.clone()));
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::errors::FastDeployError;
+use crate::predictor::Predictor;
+use crate::runtime_option::RuntimeOption;
+use crate::type_bridge::Mat;
+
+/// Latency of the timed calls. Every call predicts `batch_size` images.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    pub batch_size: usize,
+    pub iterations: usize,
+    pub mean: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    /// Images per second.
+    pub throughput: f64,
+    /// Resident memory growth over the timed calls, in bytes. Only read on Linux.
+    pub rss_growth: Option<i64>,
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "batch {:>3}: mean {:>8.2}ms p50 {:>8.2}ms p90 {:>8.2}ms p99 {:>8.2}ms {:>8.1} img/s",
+               self.batch_size, millis(self.mean), millis(self.p50), millis(self.p90), millis(self.p99), self.throughput)?;
+        if let Some(growth) = self.rss_growth {
+            write!(f, " rss {:+.1}MiB", growth as f64 / (1024.0 * 1024.0))?;
+        }
+        Ok(())
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Times `iterations` single image `predict` calls after `warmup` untimed ones, cycling through
+/// `images`.
+pub fn benchmark<P>(model: &P, images: &[&Mat], warmup: usize, iterations: usize) -> Result<BenchmarkReport, FastDeployError>
+    where P: Predictor + ?Sized {
+    benchmark_batch(model, images, 1, warmup, iterations)
+}
+
+/// Like `benchmark`, with `batch_predict` calls of `batch_size` images. A batch larger than
+/// `images` repeats them.
+pub fn benchmark_batch<P>(model: &P, images: &[&Mat], batch_size: usize, warmup: usize, iterations: usize) -> Result<BenchmarkReport, FastDeployError>
+    where P: Predictor + ?Sized {
+    if images.is_empty() {
+        return Err(FastDeployError::ConfigError("no benchmark images".to_string()));
+    }
+    let batch_size = batch_size.max(1);
+    let mut next = 0;
+    let mut run = || {
+        if batch_size == 1 {
+            model.predict(images[next])?;
+            next = (next + 1) % images.len();
+        } else {
+            let batch: Vec<&Mat> = (0..batch_size).map(|i| images[(next + i) % images.len()]).collect();
+            model.batch_predict(&batch)?;
+            next = (next + batch_size) % images.len();
+        }
+        Ok::<(), FastDeployError>(())
+    };
+    for _ in 0..warmup {
+        run()?;
+    }
+    let rss_before = resident_memory();
+    let mut latencies = Vec::with_capacity(iterations);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let call = Instant::now();
+        run()?;
+        latencies.push(call.elapsed());
+    }
+    let total = start.elapsed();
+    let rss_growth = match (rss_before, resident_memory()) {
+        (Some(before), Some(after)) => Some(after as i64 - before as i64),
+        _ => None,
+    };
+    latencies.sort();
+    let mean = if latencies.is_empty() { Duration::ZERO } else { latencies.iter().sum::<Duration>() / latencies.len() as u32 };
+    Ok(BenchmarkReport {
+        batch_size,
+        iterations,
+        mean,
+        min: latencies.first().copied().unwrap_or_default(),
+        max: latencies.last().copied().unwrap_or_default(),
+        p50: percentile(&latencies, 0.5),
+        p90: percentile(&latencies, 0.9),
+        p99: percentile(&latencies, 0.99),
+        throughput: if total.is_zero() { 0.0 } else { (iterations * batch_size) as f64 / total.as_secs_f64() },
+        rss_growth,
+    })
+}
+
+/// One report per batch size, to find where throughput stops improving.
+pub fn benchmark_batch_sizes<P>(model: &P, images: &[&Mat], batch_sizes: &[usize], warmup: usize, iterations: usize) -> Result<Vec<BenchmarkReport>, FastDeployError>
+    where P: Predictor + ?Sized {
+    batch_sizes.iter()
+        .map(|&batch_size| benchmark_batch(model, images, batch_size, warmup, iterations))
+        .collect()
+}
+
+/// Loads the model once per named `RuntimeOption`, e.g. ORT vs OpenVINO vs Paddle Inference or
+/// several thread counts, and benchmarks it. Each model is dropped before the next one is loaded.
+pub fn compare_runtime_options<M, F>(variants: &[(&str, RuntimeOption)], mut load: F, images: &[&Mat], warmup: usize, iterations: usize)
+                                     -> Result<Vec<(String, BenchmarkReport)>, FastDeployError>
+    where M: Predictor,
+          F: FnMut(&RuntimeOption) -> Result<M, FastDeployError> {
+    let mut reports = Vec::with_capacity(variants.len());
+    for (name, runtime_option) in variants {
+        let model = load(runtime_option)?;
+        reports.push((name.to_string(), benchmark(&model, images, warmup, iterations)?));
+    }
+    Ok(reports)
+}
+
+/// Nearest rank percentile of sorted latencies.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Resident set size from the `VmRSS` line of `/proc/self/status`.
+fn resident_memory() -> Option<u64> {
+    // `VmRSS:    12345 kB`
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
@@ -0,0 +1,141 @@
+use crate::errors::FastDeployError;
+use crate::image_ops::ImageOps;
+use crate::predictor::Predictor;
+use crate::result::{ClassifyResult, DetectionResult};
+use crate::type_bridge::Mat;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CascadeConfig {
+    /// Grows each side of a box by this fraction of its width/height before cropping, for context.
+    pub padding: f32,
+    /// Boxes narrower or shorter than this, in pixels, aren't classified.
+    pub min_box_size: u32,
+    /// Labels kept per box, the highest scoring first.
+    pub top_k: usize,
+    /// Crops per `batch_predict` call of the classifier.
+    pub batch_size: usize,
+}
+
+impl Default for CascadeConfig {
+    fn default() -> Self {
+        CascadeConfig { padding: 0.1, min_box_size: 8, top_k: 1, batch_size: 16 }
+    }
+}
+
+#[derive(Debug)]
+pub struct CascadeResult {
+    pub detections: DetectionResult,
+    /// One per detection, `None` for boxes under `min_box_size`.
+    pub classifications: Vec<Option<ClassifyResult>>,
+}
+
+/// Detects objects, then classifies the crop of each box, e.g. `PPYOLOE` + `PaddleClasModel` for
+/// vehicle types.
+pub struct Cascade<D, C> {
+    detector: D,
+    classifier: C,
+    image_ops: Box<dyn ImageOps>,
+    config: CascadeConfig,
+}
+
+impl<D, C> Cascade<D, C>
+    where D: Predictor<Output=DetectionResult>,
+          C: Predictor<Output=ClassifyResult> {
+    pub fn new(detector: D, classifier: C, image_ops: Box<dyn ImageOps>, config: CascadeConfig) -> Cascade<D, C> {
+        Cascade { detector, classifier, image_ops, config }
+    }
+
+    pub fn config(&self) -> &CascadeConfig {
+        &self.config
+    }
+
+    pub fn detector(&self) -> &D {
+        &self.detector
+    }
+
+    pub fn classifier(&self) -> &C {
+        &self.classifier
+    }
+
+    /// Classifies the boxes of an existing detection result.
+    pub fn classify_boxes(&self, img: &Mat, detections: &DetectionResult) -> Result<Vec<Option<ClassifyResult>>, FastDeployError> {
+        let (width, height) = self.image_ops.size(img)?;
+        let crops: Vec<(usize, [u32; 4])> = detections.boxes.iter().enumerate()
+            .filter_map(|(i, b)| crop_rect(b, width, height, &self.config).map(|rect| (i, rect)))
+            .collect();
+        let mut classifications: Vec<Option<ClassifyResult>> = detections.boxes.iter().map(|_| None).collect();
+        for chunk in crops.chunks(self.config.batch_size.max(1)) {
+            let imgs = chunk.iter()
+                .map(|(_, [x, y, w, h])| self.image_ops.crop(img, *x, *y, *w, *h))
+                .collect::<Result<Vec<Mat>, FastDeployError>>()?;
+            let img_refs: Vec<&Mat> = imgs.iter().collect();
+            let results = self.classifier.batch_predict(&img_refs)?;
+            if results.len() != chunk.len() {
+                return Err(FastDeployError::BatchSizeMismatch { expected: chunk.len(), actual: results.len() });
+            }
+            for ((i, _), result) in chunk.iter().zip(results) {
+                classifications[*i] = Some(top_k(result, self.config.top_k));
+            }
+        }
+        Ok(classifications)
+    }
+}
+
+impl<D, C> Predictor for Cascade<D, C>
+    where D: Predictor<Output=DetectionResult>,
+          C: Predictor<Output=ClassifyResult> {
+    type Output = CascadeResult;
+
+    fn predict(&self, img: &Mat) -> Result<CascadeResult, FastDeployError> {
+        let detections = self.detector.predict(img)?;
+        let classifications = self.classify_boxes(img, &detections)?;
+        Ok(CascadeResult { detections, classifications })
+    }
+}
+
+/// (x, y, width, height) of the padded box clipped to the image, `None` if the box is smaller than
+/// `min_box_size` or lies outside the image.
+fn crop_rect(b: &[f32], width: u32, height: u32, config: &CascadeConfig) -> Option<[u32; 4]> {
+    let min_box_size = config.min_box_size as f32;
+    if b[2] - b[0] < min_box_size || b[3] - b[1] < min_box_size {
+        return None;
+    }
+    let (pad_x, pad_y) = ((b[2] - b[0]) * config.padding, (b[3] - b[1]) * config.padding);
+    let x1 = (b[0] - pad_x).floor().clamp(0.0, width as f32) as u32;
+    let y1 = (b[1] - pad_y).floor().clamp(0.0, height as f32) as u32;
+    let x2 = (b[2] + pad_x).ceil().clamp(0.0, width as f32) as u32;
+    let y2 = (b[3] + pad_y).ceil().clamp(0.0, height as f32) as u32;
+    let (w, h) = (x2.saturating_sub(x1), y2.saturating_sub(y1));
+    if w == 0 || h == 0 {
+        return None;
+    }
+    Some([x1, y1, w, h])
+}
+
+fn top_k(result: ClassifyResult, k: usize) -> ClassifyResult {
+    let mut order: Vec<usize> = (0..result.scores.len()).collect();
+    order.sort_by(|&a, &b| result.scores[b].total_cmp(&result.scores[a]));
+    order.truncate(k.max(1));
+    ClassifyResult {
+        label_ids: order.iter().map(|&i| result.label_ids[i]).collect(),
+        scores: order.iter().map(|&i| result.scores[i]).collect(),
+        type_: result.type_,
+        timing: result.timing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_box_size_applies_to_the_box_before_padding() {
+        let config = CascadeConfig { padding: 0.5, min_box_size: 8, ..Default::default() };
+        // 6 px wide, 12 px once padded
+        assert_eq!(crop_rect(&[20.0, 20.0, 26.0, 40.0], 100, 100, &config), None);
+        assert_eq!(crop_rect(&[20.0, 20.0, 28.0, 40.0], 100, 100, &config), Some([16, 10, 16, 40]));
+        // clipping at the border doesn't drop a large enough box
+        assert_eq!(crop_rect(&[0.0, 0.0, 8.0, 8.0], 100, 100, &config), Some([0, 0, 12, 12]));
+        assert_eq!(crop_rect(&[120.0, 0.0, 140.0, 20.0], 100, 100, &config), None);
+    }
+}
@@ -0,0 +1,428 @@
+use serde::{Deserialize, Serialize};
+
+use crate::enum_variables::ResultType;
+use crate::errors::FastDeployError;
+use crate::result::{DetectionResult, Mask};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CocoImage {
+    pub id: u64,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CocoCategory {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Categories named after the labels, with ids starting at 1.
+pub fn categories_from_labels(names: &[&str]) -> Vec<CocoCategory> {
+    names.iter().enumerate()
+        .map(|(i, name)| CocoCategory { id: i as i64 + 1, name: name.to_string() })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RleCounts {
+    /// The compressed string of the COCO api, used for results.
+    Compressed(String),
+    Uncompressed(Vec<u32>),
+}
+
+/// Run length encoded mask, in column major order and starting with a run of zeros.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CocoRle {
+    /// (height, width)
+    pub size: [u32; 2],
+    pub counts: RleCounts,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CocoSegmentation {
+    Rle(CocoRle),
+    Polygons(Vec<Vec<f32>>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CocoAnnotation {
+    #[serde(default)]
+    pub id: u64,
+    pub image_id: u64,
+    pub category_id: i64,
+    /// (x, y, width, height)
+    pub bbox: [f32; 4],
+    #[serde(default)]
+    pub area: f32,
+    #[serde(default)]
+    pub iscrowd: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segmentation: Option<CocoSegmentation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CocoDataset {
+    #[serde(default)]
+    pub images: Vec<CocoImage>,
+    pub annotations: Vec<CocoAnnotation>,
+    #[serde(default)]
+    pub categories: Vec<CocoCategory>,
+}
+
+impl CocoDataset {
+    /// Annotates `images[i]` with `results[i]`. Label `n` is `categories[n]`. Masks, which
+    /// FastDeploy crops to their box, are pasted into the image and encoded as compressed RLE.
+    pub fn from_results(images: &[CocoImage], results: &[DetectionResult], categories: &[CocoCategory]) -> Result<CocoDataset, FastDeployError> {
+        if images.len() != results.len() {
+            return Err(FastDeployError::ConfigError(format!("{} images for {} results", images.len(), results.len())));
+        }
+        let mut annotations = vec![];
+        for (image, result) in images.iter().zip(results) {
+            let with_masks = result.contain_masks && result.masks.len() == result.scores.len();
+            for i in 0..result.scores.len() {
+                let label_id = result.label_ids[i];
+                let category = usize::try_from(label_id).ok().and_then(|label| categories.get(label))
+                    .ok_or_else(|| FastDeployError::ConfigError(format!("no category for label {}", label_id)))?;
+                let b = &result.boxes[i];
+                let bbox = [b[0], b[1], b[2] - b[0], b[3] - b[1]];
+                let (area, segmentation) = if with_masks {
+                    let mask = paste_mask(&result.masks[i], b, image.width, image.height);
+                    let area = mask.iter().filter(|&&v| v != 0).count() as f32;
+                    (area, Some(CocoSegmentation::Rle(encode_rle(&mask, image.width, image.height)?)))
+                } else {
+                    (bbox[2] * bbox[3], None)
+                };
+                annotations.push(CocoAnnotation {
+                    id: annotations.len() as u64 + 1,
+                    image_id: image.id,
+                    category_id: category.id,
+                    bbox,
+                    area,
+                    iscrowd: 0,
+                    score: Some(result.scores[i]),
+                    segmentation,
+                });
+            }
+        }
+        Ok(CocoDataset { images: images.to_vec(), annotations, categories: categories.to_vec() })
+    }
+
+    /// One result per entry of `images`, in order. Annotations without a score get 1.0. Masks are
+    /// cropped to their box like FastDeploy's, and only kept when every annotation of the image
+    /// has a segmentation.
+    pub fn to_results(&self) -> Result<Vec<DetectionResult>, FastDeployError> {
+        self.images.iter().map(|image| {
+            let annotations: Vec<&CocoAnnotation> = self.annotations.iter().filter(|a| a.image_id == image.id).collect();
+            let with_masks = !annotations.is_empty() && annotations.iter().all(|a| a.segmentation.is_some());
+            let mut result = DetectionResult { type_: ResultType::DETECTION, contain_masks: with_masks, ..Default::default() };
+            for annotation in annotations {
+                let label_id = self.categories.iter().position(|c| c.id == annotation.category_id)
+                    .ok_or_else(|| FastDeployError::ConfigError(format!("unknown category {}", annotation.category_id)))?;
+                let [x, y, w, h] = annotation.bbox;
+                let b = vec![x, y, x + w, y + h];
+                if let (true, Some(segmentation)) = (with_masks, &annotation.segmentation) {
+                    let (mask, width) = match segmentation {
+                        CocoSegmentation::Rle(rle) => (decode_rle(rle)?, rle.size[1]),
+                        CocoSegmentation::Polygons(polygons) => (fill_polygons(polygons, image.width, image.height), image.width),
+                    };
+                    result.masks.push(crop_mask(&mask, width, &b));
+                }
+                result.boxes.push(b);
+                result.scores.push(annotation.score.unwrap_or(1.0));
+                result.label_ids.push(label_id as i32);
+            }
+            Ok(result)
+        }).collect()
+    }
+
+    pub fn from_json(json: &str) -> Result<CocoDataset, FastDeployError> {
+        serde_json::from_str(json).map_err(|e| FastDeployError::ConfigError(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> Result<String, FastDeployError> {
+        serde_json::to_string(self).map_err(|e| FastDeployError::ConfigError(e.to_string()))
+    }
+
+    /// Only the annotations, the results format of the COCO evaluation tools.
+    pub fn annotations_json(&self) -> Result<String, FastDeployError> {
+        serde_json::to_string(&self.annotations).map_err(|e| FastDeployError::ConfigError(e.to_string()))
+    }
+}
+
+/// Compressed RLE of a row major `width` x `height` mask.
+pub fn encode_rle(mask: &[u8], width: u32, height: u32) -> Result<CocoRle, FastDeployError> {
+    let (width, height) = (width as usize, height as usize);
+    if mask.len() != width * height {
+        return Err(FastDeployError::ConfigError(format!("mask of {} values is not {}x{}", mask.len(), width, height)));
+    }
+    let mut counts = vec![];
+    let mut current = 0;
+    let mut run = 0u32;
+    for x in 0..width {
+        for y in 0..height {
+            let v = (mask[y * width + x] != 0) as u8;
+            if v != current {
+                counts.push(run);
+                current = v;
+                run = 0;
+            }
+            run += 1;
+        }
+    }
+    counts.push(run);
+    Ok(CocoRle { size: [height as u32, width as u32], counts: RleCounts::Compressed(compress_counts(&counts)) })
+}
+
+/// Row major mask of `rle`, with 1 for the object.
+pub fn decode_rle(rle: &CocoRle) -> Result<Vec<u8>, FastDeployError> {
+    let [height, width] = [rle.size[0] as usize, rle.size[1] as usize];
+    let counts = match &rle.counts {
+        RleCounts::Compressed(s) => decompress_counts(s)?,
+        RleCounts::Uncompressed(counts) => counts.clone(),
+    };
+    let mut mask = vec![0u8; width * height];
+    let mut position = 0;
+    for (i, &count) in counts.iter().enumerate() {
+        let end = position + count as usize;
+        if end > mask.len() {
+            return Err(FastDeployError::ConfigError("rle counts exceed the mask size".to_string()));
+        }
+        if i % 2 == 1 {
+            for p in position..end {
+                // column major position to row major index
+                mask[(p % height) * width + p / height] = 1;
+            }
+        }
+        position = end;
+    }
+    Ok(mask)
+}
+
+// the string encoding of the COCO api: differences to the count two runs back, in 5 bit groups
+fn compress_counts(counts: &[u32]) -> String {
+    let mut s = String::new();
+    for i in 0..counts.len() {
+        let mut x = counts[i] as i64;
+        if i > 2 {
+            x -= counts[i - 2] as i64;
+        }
+        loop {
+            let mut c = x & 0x1f;
+            x >>= 5;
+            let more = if c & 0x10 != 0 { x != -1 } else { x != 0 };
+            if more {
+                c |= 0x20;
+            }
+            s.push((c as u8 + 48) as char);
+            if !more {
+                break;
+            }
+        }
+    }
+    s
+}
+
+fn decompress_counts(s: &str) -> Result<Vec<u32>, FastDeployError> {
+    let bytes = s.as_bytes();
+    let mut counts: Vec<u32> = vec![];
+    let mut p = 0;
+    while p < bytes.len() {
+        let mut x = 0i64;
+        let mut k = 0;
+        loop {
+            let c = bytes[p].checked_sub(48)
+                .ok_or_else(|| FastDeployError::ConfigError("invalid rle string".to_string()))? as i64;
+            x |= (c & 0x1f) << (5 * k);
+            p += 1;
+            k += 1;
+            if c & 0x20 == 0 {
+                if c & 0x10 != 0 {
+                    x |= -1i64 << (5 * k);
+                }
+                break;
+            }
+            if p >= bytes.len() || k > 12 {
+                return Err(FastDeployError::ConfigError("invalid rle string".to_string()));
+            }
+        }
+        if counts.len() > 2 {
+            x += counts[counts.len() - 2] as i64;
+        }
+        counts.push(u32::try_from(x).map_err(|_| FastDeployError::ConfigError("invalid rle string".to_string()))?);
+    }
+    Ok(counts)
+}
+
+/// Places a box sized mask at its box in a `width` x `height` image.
+fn paste_mask(mask: &Mask, b: &[f32], width: u32, height: u32) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let mut image = vec![0u8; width * height];
+    let (mask_h, mask_w) = match mask.shape[..] {
+        [h, w] => (h.max(0) as usize, w.max(0) as usize),
+        _ => return image,
+    };
+    let (x0, y0) = (b[0].max(0.0) as usize, b[1].max(0.0) as usize);
+    for y in 0..mask_h.min(height.saturating_sub(y0)) {
+        for x in 0..mask_w.min(width.saturating_sub(x0)) {
+            image[(y0 + y) * width + x0 + x] = (mask.data[y * mask_w + x] != 0) as u8;
+        }
+    }
+    image
+}
+
+fn crop_mask(image: &[u8], width: u32, b: &[f32]) -> Mask {
+    let width = width as usize;
+    let height = image.len() / width.max(1);
+    let (x0, y0) = ((b[0].max(0.0) as usize).min(width), (b[1].max(0.0) as usize).min(height));
+    let (x1, y1) = ((b[2].max(0.0) as usize).min(width), (b[3].max(0.0) as usize).min(height));
+    let (mask_w, mask_h) = (x1.saturating_sub(x0), y1.saturating_sub(y0));
+    let mut data = Vec::with_capacity(mask_w * mask_h);
+    for y in y0..y0 + mask_h {
+        data.extend_from_slice(&image[y * width + x0..y * width + x0 + mask_w]);
+    }
+    Mask { data, shape: vec![mask_h as i64, mask_w as i64], type_: ResultType::MASK }
+}
+
+/// Rasterizes the union of `[x1, y1, x2, y2, ...]` polygons, sampled at pixel centers.
+fn fill_polygons(polygons: &[Vec<f32>], width: u32, height: u32) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let mut mask = vec![0u8; width * height];
+    for polygon in polygons {
+        let points: Vec<(f32, f32)> = polygon.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+        if points.len() < 3 {
+            continue;
+        }
+        for y in 0..height {
+            let cy = y as f32 + 0.5;
+            let mut crossings = vec![];
+            for (i, &(xa, ya)) in points.iter().enumerate() {
+                let (xb, yb) = points[(i + 1) % points.len()];
+                if (ya <= cy) != (yb <= cy) {
+                    crossings.push(xa + (cy - ya) / (yb - ya) * (xb - xa));
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                let start = (pair[0] - 0.5).ceil().max(0.0) as usize;
+                let end = ((pair[1] - 0.5).floor() + 1.0).clamp(0.0, width as f32) as usize;
+                for x in start..end {
+                    mask[y * width + x] = 1;
+                }
+            }
+        }
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compressed(rle: &CocoRle) -> &str {
+        match &rle.counts {
+            RleCounts::Compressed(s) => s,
+            RleCounts::Uncompressed(_) => panic!("expected compressed counts"),
+        }
+    }
+
+    fn box_mask(b: &[usize; 4], width: usize, height: usize) -> Vec<u8> {
+        let mut mask = vec![0u8; width * height];
+        for y in b[1]..b[3] {
+            for x in b[0]..b[2] {
+                mask[y * width + x] = 1;
+            }
+        }
+        mask
+    }
+
+    #[test]
+    fn encode_rle_matches_pycocotools() {
+        // strings as pycocotools.mask.encode writes them for the same masks
+        assert_eq!(compressed(&encode_rle(&[1, 0, 0, 0], 2, 2).unwrap()), "013");
+        let rle = encode_rle(&box_mask(&[0, 0, 3, 3], 40, 20), 40, 20).unwrap();
+        assert_eq!(rle.size, [20, 40]);
+        assert_eq!(compressed(&rle), "03a0000Tg0");
+        assert_eq!(compressed(&encode_rle(&box_mask(&[12, 6, 15, 8], 40, 20), 40, 20).unwrap()), "f72b0000^?");
+    }
+
+    #[test]
+    fn encode_rle_rejects_a_mask_of_the_wrong_size() {
+        assert!(encode_rle(&[1, 0, 0], 2, 2).is_err());
+        assert!(encode_rle(&[0; 5], 2, 2).is_err());
+    }
+
+    #[test]
+    fn decode_rle_reads_compressed_and_uncompressed_counts() {
+        let rle = CocoRle { size: [20, 40], counts: RleCounts::Compressed("f72b0000^?".to_string()) };
+        assert_eq!(decode_rle(&rle).unwrap(), box_mask(&[12, 6, 15, 8], 40, 20));
+        let rle = CocoRle { size: [2, 2], counts: RleCounts::Uncompressed(vec![0, 1, 3]) };
+        assert_eq!(decode_rle(&rle).unwrap(), vec![1, 0, 0, 0]);
+        let rle = CocoRle { size: [2, 2], counts: RleCounts::Uncompressed(vec![0, 5]) };
+        assert!(decode_rle(&rle).is_err());
+    }
+
+    #[test]
+    fn mask_rle_mask_round_trip() {
+        let (width, height) = (37u32, 23u32);
+        let mut seed = 7u32;
+        let mask: Vec<u8> = (0..width * height)
+            .map(|i| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                // long runs of ones to exercise the multi character counts
+                (i % 50 >= 30 || (seed >> 16) % 5 == 1) as u8
+            })
+            .collect();
+        let rle = encode_rle(&mask, width, height).unwrap();
+        let json = serde_json::to_string(&rle).unwrap();
+        assert_eq!(decode_rle(&serde_json::from_str(&json).unwrap()).unwrap(), mask);
+    }
+
+    #[test]
+    fn results_round_trip() {
+        let mut mask = Mask { data: vec![0; 6 * 4], shape: vec![4, 6], type_: ResultType::MASK };
+        for y in 1..3 {
+            for x in 2..5 {
+                mask.data[y * 6 + x] = 1;
+            }
+        }
+        let result = DetectionResult {
+            boxes: vec![vec![10.0, 5.0, 16.0, 9.0], vec![0.0, 0.0, 3.0, 3.0]],
+            scores: vec![0.9, 0.4],
+            label_ids: vec![1, 0],
+            masks: vec![mask.clone(), Mask { data: vec![1; 9], shape: vec![3, 3], type_: ResultType::MASK }],
+            contain_masks: true,
+            type_: ResultType::DETECTION,
+            ..Default::default()
+        };
+        let images = vec![CocoImage { id: 42, width: 40, height: 20, file_name: None }];
+        let dataset = CocoDataset::from_results(&images, &[result], &categories_from_labels(&["person", "car"])).unwrap();
+        let annotation = &dataset.annotations[0];
+        assert_eq!((annotation.image_id, annotation.category_id, annotation.bbox, annotation.area), (42, 2, [10.0, 5.0, 6.0, 4.0], 6.0));
+        assert!(matches!(&annotation.segmentation, Some(CocoSegmentation::Rle(rle)) if compressed(rle) == "f72b0000^?"));
+
+        let results = CocoDataset::from_json(&dataset.to_json().unwrap()).unwrap().to_results().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].boxes, vec![vec![10.0, 5.0, 16.0, 9.0], vec![0.0, 0.0, 3.0, 3.0]]);
+        assert_eq!((results[0].scores.clone(), results[0].label_ids.clone()), (vec![0.9, 0.4], vec![1, 0]));
+        assert!(results[0].contain_masks);
+        assert_eq!((results[0].masks[0].shape.clone(), results[0].masks[0].data.clone()), (mask.shape, mask.data));
+        assert_eq!(results[0].masks[1].data, vec![1; 9]);
+    }
+
+    #[test]
+    fn polygons_are_filled_and_cropped_to_the_box() {
+        let json = r#"{"images":[{"id":1,"width":10,"height":10}],"categories":[{"id":3,"name":"a"}],
+            "annotations":[{"image_id":1,"category_id":3,"bbox":[2,2,4,4],"segmentation":[[2,2,6,2,6,6,2,6]]}]}"#;
+        let results = CocoDataset::from_json(json).unwrap().to_results().unwrap();
+        assert_eq!(results[0].label_ids, vec![0]);
+        assert_eq!(results[0].scores, vec![1.0]);
+        assert_eq!((results[0].masks[0].shape.clone(), results[0].masks[0].data.clone()), (vec![4, 4], vec![1; 16]));
+    }
+}
@@ -0,0 +1,576 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::errors::FastDeployError;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConfigKind {
+    /// PaddleDetection `infer_cfg.yml`
+    Detection,
+    /// PaddleClas `inference_cfg.yml`
+    Classification,
+    /// PaddleSeg `deploy.yaml`
+    Segmentation,
+}
+
+/// Settings of an exported Paddle model that the runtime reads from its config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeployConfig {
+    pub kind: ConfigKind,
+    /// Only PaddleDetection exports record the architecture.
+    pub arch: Option<String>,
+    /// RCNN exports set this when the model has a mask head.
+    pub mask: bool,
+    pub label_list: Vec<String>,
+    /// Network input size as (width, height).
+    pub target_size: Option<(u32, u32)>,
+    pub mean: Option<[f32; 3]>,
+    pub std: Option<[f32; 3]>,
+    pub draw_threshold: Option<f32>,
+}
+
+/// Preprocessing overrides for config driven Paddle models. FastDeploy builds the preprocessing
+/// from the deploy config, so they are applied by writing a modified copy of it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PreprocessConfig {
+    /// Network input size as (width, height).
+    pub target_size: Option<(u32, u32)>,
+    /// Only used by PaddleDetection `Resize`.
+    pub keep_ratio: Option<bool>,
+    pub mean: Option<[f32; 3]>,
+    pub std: Option<[f32; 3]>,
+    /// Drops the normalize op, for models that normalize inside the network.
+    pub disable_normalize: bool,
+    /// Fill value of the PaddleDetection `Pad` op.
+    pub pad_value: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct DetectionFile {
+    arch: String,
+    #[serde(default)]
+    mask: bool,
+    #[serde(default)]
+    label_list: Vec<String>,
+    draw_threshold: Option<f32>,
+    #[serde(rename = "Preprocess", default)]
+    preprocess: Vec<Mapping>,
+}
+
+#[derive(Deserialize)]
+struct ClassificationFile {
+    #[serde(rename = "PreProcess")]
+    preprocess: ClassificationPreProcess,
+    #[serde(rename = "PostProcess", default)]
+    postprocess: Mapping,
+}
+
+#[derive(Deserialize)]
+struct ClassificationPreProcess {
+    #[serde(default)]
+    transform_ops: Vec<Mapping>,
+}
+
+#[derive(Deserialize)]
+struct SegmentationFile {
+    #[serde(rename = "Deploy")]
+    deploy: SegmentationDeploy,
+}
+
+#[derive(Deserialize)]
+struct SegmentationDeploy {
+    #[serde(default)]
+    transforms: Vec<Mapping>,
+}
+
+impl DeployConfig {
+    /// Parses a config file, telling the three formats apart by their top level keys.
+    pub fn from_file(path: &str) -> Result<DeployConfig, FastDeployError> {
+        Self::from_content(path, &read_config(path)?)
+    }
+
+    /// Parses a rewritten config, resolving relative paths like the label file of PaddleClas
+    /// against the directory of the original one.
+    pub fn from_override(config: &ConfigOverride) -> Result<DeployConfig, FastDeployError> {
+        Self::from_content(&config.original, &read_config(&config.path)?)
+    }
+
+    fn from_content(path: &str, content: &str) -> Result<DeployConfig, FastDeployError> {
+        let value: Value = serde_yaml::from_str(content).map_err(|e| config_error(path, e))?;
+        let kind = if value.get("Preprocess").is_some() || value.get("arch").is_some() {
+            ConfigKind::Detection
+        } else if value.get("PreProcess").is_some() {
+            ConfigKind::Classification
+        } else if value.get("Deploy").is_some() {
+            ConfigKind::Segmentation
+        } else {
+            return Err(FastDeployError::ConfigError(format!("{}: unknown config layout", path)));
+        };
+        Self::parse(kind, path, content)
+    }
+
+    pub fn from_detection_file(path: &str) -> Result<DeployConfig, FastDeployError> {
+        Self::parse(ConfigKind::Detection, path, &read_config(path)?)
+    }
+
+    pub fn from_classification_file(path: &str) -> Result<DeployConfig, FastDeployError> {
+        Self::parse(ConfigKind::Classification, path, &read_config(path)?)
+    }
+
+    pub fn from_segmentation_file(path: &str) -> Result<DeployConfig, FastDeployError> {
+        Self::parse(ConfigKind::Segmentation, path, &read_config(path)?)
+    }
+
+    pub fn label_name(&self, label_id: i32) -> Option<&str> {
+        usize::try_from(label_id).ok()
+            .and_then(|i| self.label_list.get(i))
+            .map(|s| s.as_str())
+    }
+
+    fn parse(kind: ConfigKind, path: &str, content: &str) -> Result<DeployConfig, FastDeployError> {
+        match kind {
+            ConfigKind::Detection => {
+                let file: DetectionFile = serde_yaml::from_str(content).map_err(|e| config_error(path, e))?;
+                let mut config = Self::empty(kind);
+                config.arch = Some(file.arch);
+                config.mask = file.mask;
+                config.label_list = file.label_list;
+                config.draw_threshold = file.draw_threshold;
+                for op in &file.preprocess {
+                    match op_type(op) {
+                        // PaddleDetection sizes are [h, w]
+                        Some("Resize") | Some("LetterBoxResize") => {
+                            config.target_size = size_field(op, "target_size").map(|(h, w)| (w, h));
+                        }
+                        Some("NormalizeImage") => config.read_mean_std(op),
+                        _ => {}
+                    }
+                }
+                Ok(config)
+            }
+            ConfigKind::Classification => {
+                let file: ClassificationFile = serde_yaml::from_str(content).map_err(|e| config_error(path, e))?;
+                let mut config = Self::empty(kind);
+                for op in &file.preprocess.transform_ops {
+                    // PaddleClas ops are single key maps, e.g. `- CropImage: {size: 224}`
+                    for (name, args) in op {
+                        match (name.as_str(), args.as_mapping()) {
+                            (Some("ResizeImage"), Some(args)) if config.target_size.is_none() => {
+                                config.target_size = size_field(args, "size");
+                            }
+                            (Some("CropImage"), Some(args)) => config.target_size = size_field(args, "size"),
+                            (Some("NormalizeImage"), Some(args)) => config.read_mean_std(args),
+                            _ => {}
+                        }
+                    }
+                }
+                let label_file = file.postprocess.get("Topk")
+                    .and_then(|topk| topk.get("class_id_map_file"))
+                    .and_then(|f| f.as_str());
+                if let Some(label_file) = label_file {
+                    config.label_list = read_class_id_map(path, label_file);
+                }
+                Ok(config)
+            }
+            ConfigKind::Segmentation => {
+                let file: SegmentationFile = serde_yaml::from_str(content).map_err(|e| config_error(path, e))?;
+                let mut config = Self::empty(kind);
+                for op in &file.deploy.transforms {
+                    match op_type(op) {
+                        // PaddleSeg sizes are [w, h]
+                        Some("Resize") => config.target_size = size_field(op, "target_size"),
+                        Some("Normalize") => {
+                            // PaddleSeg normalizes with 0.5 when mean/std are omitted
+                            config.mean = triple_field(op, "mean").or(Some([0.5; 3]));
+                            config.std = triple_field(op, "std").or(Some([0.5; 3]));
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(config)
+            }
+        }
+    }
+
+    fn empty(kind: ConfigKind) -> DeployConfig {
+        DeployConfig {
+            kind,
+            arch: None,
+            mask: false,
+            label_list: vec![],
+            target_size: None,
+            mean: None,
+            std: None,
+            draw_threshold: None,
+        }
+    }
+
+    fn read_mean_std(&mut self, op: &Mapping) {
+        self.mean = triple_field(op, "mean");
+        self.std = triple_field(op, "std");
+    }
+}
+
+impl PreprocessConfig {
+    /// Writes `config_path` with these overrides applied to a new file in the temp dir, which is
+    /// removed when the returned `ConfigOverride` is dropped.
+    pub fn write_config(&self, config_path: &str) -> Result<ConfigOverride, FastDeployError> {
+        self.write_override(config_path, config_path)
+    }
+
+    fn write_override(&self, config_path: &str, original: &str) -> Result<ConfigOverride, FastDeployError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let content = read_config(config_path)?;
+        let mut value: Value = serde_yaml::from_str(&content).map_err(|e| config_error(config_path, e))?;
+        if let Some(ops) = value.get_mut("Preprocess").and_then(|v| v.as_sequence_mut()) {
+            self.rewrite_detection(ops);
+        } else if let Some(ops) = value.get_mut("PreProcess").and_then(|v| v.get_mut("transform_ops")).and_then(|v| v.as_sequence_mut()) {
+            self.rewrite_classification(ops);
+        } else if let Some(ops) = value.get_mut("Deploy").and_then(|v| v.get_mut("transforms")).and_then(|v| v.as_sequence_mut()) {
+            self.rewrite_segmentation(ops);
+        } else {
+            return Err(FastDeployError::ConfigError(format!("{}: unknown config layout", config_path)));
+        }
+        let content = serde_yaml::to_string(&value).map_err(|e| config_error(config_path, e))?;
+        let file_name = Path::new(original).file_name().and_then(|n| n.to_str()).unwrap_or("deploy.yml");
+        let path = std::env::temp_dir().join(format!("fastdeploy-rs-{}-{}-{}", process::id(),
+                                                     COUNTER.fetch_add(1, Ordering::Relaxed), file_name));
+        let path = path.to_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| FastDeployError::IoError(format!("non utf-8 path: {}", path.display())))?;
+        fs::write(&path, content).map_err(|e| FastDeployError::IoError(format!("{}: {}", path, e)))?;
+        Ok(ConfigOverride { path, original: original.to_string() })
+    }
+    fn rewrite_detection(&self, ops: &mut Vec<Value>) {
+        if self.disable_normalize {
+            ops.retain(|op| op.get("type").and_then(|t| t.as_str()) != Some("NormalizeImage"));
+        }
+        for op in ops.iter_mut().filter_map(|op| op.as_mapping_mut()) {
+            match op_type(op).map(|t| t.to_string()).as_deref() {
+                Some("Resize") | Some("LetterBoxResize") => {
+                    // PaddleDetection sizes are [h, w]
+                    if let Some((w, h)) = self.target_size {
+                        op.insert("target_size".into(), pair(h, w));
+                    }
+                    if let Some(keep_ratio) = self.keep_ratio {
+                        op.insert("keep_ratio".into(), keep_ratio.into());
+                    }
+                }
+                Some("NormalizeImage") => self.write_mean_std(op),
+                Some("Pad") => {
+                    if let Some(pad_value) = self.pad_value {
+                        op.insert("fill_value".into(), triple([pad_value; 3]));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn rewrite_classification(&self, ops: &mut Vec<Value>) {
+        if self.disable_normalize {
+            ops.retain(|op| op.get("NormalizeImage").is_none());
+        }
+        let crop = ops.iter()
+            .find_map(|op| op.get("CropImage").and_then(|args| args.as_mapping()).and_then(|args| size_field(args, "size")));
+        for op in ops.iter_mut().filter_map(|op| op.as_mapping_mut()) {
+            for (name, args) in op.iter_mut() {
+                let args = match args.as_mapping_mut() {
+                    Some(args) => args,
+                    None => continue,
+                };
+                match (name.as_str(), self.target_size) {
+                    (Some("CropImage"), Some((w, h))) => {
+                        args.insert("size".into(), pair(w, h));
+                    }
+                    (Some("ResizeImage"), Some((w, h))) => {
+                        match (args.get("resize_short").and_then(number), crop) {
+                            // keep the resize/crop ratio of the export, e.g. 256/224
+                            (Some(short), Some((crop_w, crop_h))) => {
+                                let scale = w.min(h) as f32 / crop_w.min(crop_h) as f32;
+                                args.insert("resize_short".into(), ((short * scale).round() as u32).into());
+                            }
+                            (Some(_), None) => {
+                                args.insert("resize_short".into(), w.min(h).into());
+                            }
+                            _ => {
+                                args.insert("size".into(), pair(w, h));
+                            }
+                        }
+                    }
+                    (Some("NormalizeImage"), _) => self.write_mean_std(args),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn rewrite_segmentation(&self, ops: &mut Vec<Value>) {
+        if self.disable_normalize {
+            ops.retain(|op| op.get("type").and_then(|t| t.as_str()) != Some("Normalize"));
+        }
+        for op in ops.iter_mut().filter_map(|op| op.as_mapping_mut()) {
+            match op_type(op).map(|t| t.to_string()).as_deref() {
+                // PaddleSeg sizes are [w, h]
+                Some("Resize") => {
+                    if let Some((w, h)) = self.target_size {
+                        op.insert("target_size".into(), pair(w, h));
+                    }
+                }
+                Some("Normalize") => self.write_mean_std(op),
+                _ => {}
+            }
+        }
+    }
+
+    fn write_mean_std(&self, op: &mut Mapping) {
+        if let Some(mean) = self.mean {
+            op.insert("mean".into(), triple(mean));
+        }
+        if let Some(std) = self.std {
+            op.insert("std".into(), triple(std));
+        }
+    }
+}
+
+fn pair(a: u32, b: u32) -> Value {
+    Value::Sequence(vec![a.into(), b.into()])
+}
+
+fn triple(values: [f32; 3]) -> Value {
+    Value::Sequence(values.iter().map(|&v| (v as f64).into()).collect())
+}
+
+/// A rewritten copy of a deploy config, removed on drop.
+#[derive(Debug)]
+pub struct ConfigOverride {
+    path: String,
+    original: String,
+}
+
+impl ConfigOverride {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The config this copy was rewritten from.
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    /// Applies more overrides on top of this copy, into a new one.
+    pub fn rewrite(&self, preprocess: &PreprocessConfig) -> Result<ConfigOverride, FastDeployError> {
+        preprocess.write_override(&self.path, &self.original)
+    }
+}
+
+impl Drop for ConfigOverride {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn read_config(path: &str) -> Result<String, FastDeployError> {
+    fs::read_to_string(path).map_err(|e| FastDeployError::IoError(format!("{}: {}", path, e)))
+}
+
+fn config_error(path: &str, e: serde_yaml::Error) -> FastDeployError {
+    FastDeployError::ConfigError(format!("{}: {}", path, e))
+}
+
+fn op_type(op: &Mapping) -> Option<&str> {
+    op.get("type").and_then(|t| t.as_str())
+}
+
+fn number(value: &Value) -> Option<f32> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|n| n as f32),
+        // PaddleClas writes scales as expressions such as `1.0/255.0`
+        Value::String(s) => match s.split_once('/') {
+            Some((a, b)) => Some(a.trim().parse::<f32>().ok()? / b.trim().parse::<f32>().ok()?),
+            None => s.trim().parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+/// Reads either a square `size: 224` or a `[a, b]` pair.
+fn size_field(op: &Mapping, key: &str) -> Option<(u32, u32)> {
+    match op.get(key)? {
+        Value::Sequence(seq) if seq.len() == 2 => {
+            Some((number(&seq[0])? as u32, number(&seq[1])? as u32))
+        }
+        value => number(value).map(|s| (s as u32, s as u32)),
+    }
+}
+
+fn triple_field(op: &Mapping, key: &str) -> Option<[f32; 3]> {
+    match op.get(key)? {
+        Value::Sequence(seq) if seq.len() == 3 => {
+            Some([number(&seq[0])?, number(&seq[1])?, number(&seq[2])?])
+        }
+        _ => None,
+    }
+}
+
+/// PaddleClas label files hold `<class id> <label>` per line. The path in the exported config is
+/// often relative to the training repo, so a missing file yields an empty label list.
+fn read_class_id_map(config_path: &str, label_file: &str) -> Vec<String> {
+    let candidates = [
+        Path::new(config_path).parent().map(|dir| dir.join(label_file)),
+        Path::new(config_path).parent().and_then(|dir| Path::new(label_file).file_name().map(|name| dir.join(name))),
+        Some(Path::new(label_file).to_path_buf()),
+    ];
+    let content = match candidates.iter().flatten().find_map(|p| fs::read_to_string(p).ok()) {
+        Some(content) => content,
+        None => return vec![],
+    };
+    let mut labels = BTreeMap::new();
+    for line in content.lines() {
+        if let Some((id, label)) = line.trim().split_once(char::is_whitespace) {
+            if let Ok(id) = id.parse::<usize>() {
+                labels.insert(id, label.trim().to_string());
+            }
+        }
+    }
+    let len = labels.keys().next_back().map_or(0, |max| max + 1);
+    let mut label_list = vec![String::new(); len];
+    for (id, label) in labels {
+        label_list[id] = label;
+    }
+    label_list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DETECTION: &str = "
+mode: paddle
+draw_threshold: 0.5
+metric: COCO
+use_dynamic_shape: false
+arch: YOLO
+min_subgraph_size: 3
+Preprocess:
+- interp: 2
+  keep_ratio: false
+  target_size: [320, 640]
+  type: Resize
+- is_scale: true
+  mean: [0.485, 0.456, 0.406]
+  std: [0.229, 0.224, 0.225]
+  type: NormalizeImage
+- type: Permute
+label_list:
+- person
+- bicycle
+";
+
+    const CLASSIFICATION: &str = "
+PreProcess:
+  transform_ops:
+  - ResizeImage:
+      resize_short: 256
+  - CropImage:
+      size: 224
+  - NormalizeImage:
+      channel_num: 3
+      mean: [0.485, 0.456, 0.406]
+      order: ''
+      scale: 0.00392157
+      std: [0.229, 0.224, 0.225]
+  - ToCHWImage: null
+PostProcess:
+  Topk:
+    topk: 5
+    class_id_map_file: ../ppcls/utils/imagenet1k_label_list.txt
+";
+
+    const SEGMENTATION: &str = "
+Deploy:
+  model: model.pdmodel
+  params: model.pdiparams
+  transforms:
+  - target_size: [512, 256]
+    type: Resize
+  - type: Normalize
+";
+
+    #[test]
+    fn detection_target_size_is_swapped_to_width_height() {
+        let config = DeployConfig::parse(ConfigKind::Detection, "infer_cfg.yml", DETECTION).unwrap();
+        assert_eq!(config.arch.as_deref(), Some("YOLO"));
+        assert_eq!(config.target_size, Some((640, 320)));
+        assert_eq!(config.mean, Some([0.485, 0.456, 0.406]));
+        assert_eq!(config.std, Some([0.229, 0.224, 0.225]));
+        assert_eq!(config.label_name(1), Some("bicycle"));
+        assert_eq!(config.label_name(2), None);
+        assert_eq!(config.draw_threshold, Some(0.5));
+    }
+
+    #[test]
+    fn classification_reads_crop_size_and_class_id_map() {
+        let dir = std::env::temp_dir().join(format!("fastdeploy-rs-config-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // only the file name of the training repo path exists next to the config
+        fs::write(dir.join("imagenet1k_label_list.txt"), "0 tench\n2 goldfish\n").unwrap();
+        let path = dir.join("inference_cfg.yml");
+        let config = DeployConfig::parse(ConfigKind::Classification, path.to_str().unwrap(), CLASSIFICATION).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(config.target_size, Some((224, 224)));
+        assert_eq!(config.mean, Some([0.485, 0.456, 0.406]));
+        assert_eq!(config.label_list, vec!["tench", "", "goldfish"]);
+    }
+
+    #[test]
+    fn classification_without_class_id_map_has_no_labels() {
+        let config = DeployConfig::parse(ConfigKind::Classification, "/nonexistent/inference_cfg.yml", CLASSIFICATION).unwrap();
+        assert!(config.label_list.is_empty());
+    }
+
+    #[test]
+    fn segmentation_keeps_width_height_and_default_normalize() {
+        let config = DeployConfig::parse(ConfigKind::Segmentation, "deploy.yaml", SEGMENTATION).unwrap();
+        assert_eq!(config.target_size, Some((512, 256)));
+        assert_eq!(config.mean, Some([0.5; 3]));
+        assert_eq!(config.std, Some([0.5; 3]));
+    }
+
+    #[test]
+    fn overrides_stack_resolve_labels_next_to_the_original_and_are_removed() {
+        let dir = std::env::temp_dir().join(format!("fastdeploy-rs-override-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("imagenet1k_label_list.txt"), "0 tench\n1 goldfish\n").unwrap();
+        let path = dir.join("inference_cfg.yml");
+        fs::write(&path, CLASSIFICATION).unwrap();
+        let size = PreprocessConfig { target_size: Some((320, 320)), ..Default::default() };
+        let first = size.write_config(path.to_str().unwrap()).unwrap();
+        let mean = PreprocessConfig { mean: Some([0.5; 3]), ..Default::default() };
+        let second = first.rewrite(&mean).unwrap();
+        let first_path = first.path().to_string();
+        drop(first);
+        assert!(!Path::new(&first_path).exists());
+        assert_eq!(second.original(), path.to_str().unwrap());
+        let config = DeployConfig::from_override(&second).unwrap();
+        let second_path = second.path().to_string();
+        drop(second);
+        assert!(!Path::new(&second_path).exists());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(config.target_size, Some((320, 320)));
+        assert_eq!(config.mean, Some([0.5; 3]));
+        assert_eq!(config.label_list, vec!["tench", "goldfish"]);
+    }
+
+    #[test]
+    fn number_parses_expressions() {
+        assert_eq!(number(&Value::String("1.0/255.0".to_string())), Some(1.0 / 255.0));
+        assert_eq!(number(&Value::from(3)), Some(3.0));
+    }
+}
@@ -0,0 +1,265 @@
+use std::sync::Arc;
+
+use crate::errors::FastDeployError;
+use crate::image_ops::ImageTransform;
+use crate::postprocess::{concat, nms, select, sort_by_score};
+use crate::predictor::Predictor;
+use crate::result::DetectionResult;
+use crate::type_bridge::Mat;
+
+pub type SharedDetector = Arc<dyn Predictor<Output=DetectionResult>>;
+
+/// Test time augmentation applied to the input of an ensemble member. The boxes are mapped back
+/// to the original image, masks and rotated boxes are dropped.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Augmentation {
+    Identity,
+    HorizontalFlip,
+    /// Resizes the image by this factor, which must be positive and finite.
+    Scale(f32),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FusionMode {
+    /// Weighted Box Fusion: averages the overlapping boxes weighted by their scores.
+    Wbf,
+    /// Keeps the best of the overlapping boxes, per label.
+    Nms,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EnsembleConfig {
+    pub fusion: FusionMode,
+    pub iou_threshold: f32,
+    /// Boxes scoring lower are ignored before fusion.
+    pub skip_score_threshold: f32,
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        EnsembleConfig { fusion: FusionMode::Wbf, iou_threshold: 0.55, skip_score_threshold: 0.0 }
+    }
+}
+
+struct Member {
+    model: SharedDetector,
+    augmentation: Augmentation,
+    weight: f32,
+}
+
+/// Fuses the detections of several detectors, or of one detector on augmented inputs.
+pub struct Ensemble {
+    config: EnsembleConfig,
+    members: Vec<Member>,
+    image_ops: Option<Box<dyn ImageTransform>>,
+}
+
+impl Ensemble {
+    pub fn new(config: EnsembleConfig) -> Ensemble {
+        Ensemble { config, members: vec![], image_ops: None }
+    }
+
+    /// Needed by members with an augmentation other than `Identity`.
+    pub fn with_image_ops(mut self, image_ops: Box<dyn ImageTransform>) -> Ensemble {
+        self.image_ops = Some(image_ops);
+        self
+    }
+
+    /// Members with a weight of 0 or less are ignored by the fusion.
+    pub fn add(self, model: SharedDetector, weight: f32) -> Ensemble {
+        self.add_augmented(model, Augmentation::Identity, weight)
+    }
+
+    /// Adding the same model with several augmentations runs it once per augmentation.
+    pub fn add_augmented(mut self, model: SharedDetector, augmentation: Augmentation, weight: f32) -> Ensemble {
+        self.members.push(Member { model, augmentation, weight });
+        self
+    }
+
+    fn predict_member(&self, member: &Member, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        if member.augmentation == Augmentation::Identity {
+            return member.model.predict(img);
+        }
+        let image_ops = self.image_ops.as_ref()
+            .ok_or_else(|| FastDeployError::ConfigError("augmented ensemble members need image ops".to_string()))?;
+        let (width, height) = image_ops.size(img)?;
+        let mut result = match member.augmentation {
+            Augmentation::HorizontalFlip => {
+                let mut result = member.model.predict(&image_ops.flip_horizontal(img)?)?;
+                for b in &mut result.boxes {
+                    let (x1, x2) = (b[0], b[2]);
+                    b[0] = width as f32 - x2;
+                    b[2] = width as f32 - x1;
+                }
+                result
+            }
+            Augmentation::Scale(scale) => {
+                if !(scale.is_finite() && scale > 0.0) {
+                    return Err(FastDeployError::ConfigError(format!("invalid augmentation scale {}", scale)));
+                }
+                let resized = image_ops.resize(img, ((width as f32 * scale).round() as u32).max(1),
+                                               ((height as f32 * scale).round() as u32).max(1))?;
+                let mut result = member.model.predict(&resized)?;
+                for v in result.boxes.iter_mut().flatten() {
+                    *v /= scale;
+                }
+                result
+            }
+            Augmentation::Identity => unreachable!(),
+        };
+        result.rotated_boxes.clear();
+        result.masks.clear();
+        result.contain_masks = false;
+        Ok(result)
+    }
+}
+
+impl Predictor for Ensemble {
+    type Output = DetectionResult;
+
+    fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let results = self.members.iter()
+            .map(|member| self.predict_member(member, img))
+            .collect::<Result<Vec<_>, FastDeployError>>()?;
+        let weights: Vec<f32> = self.members.iter().map(|m| m.weight).collect();
+        Ok(match self.config.fusion {
+            FusionMode::Wbf => weighted_boxes_fusion(&results, &weights, self.config.iou_threshold, self.config.skip_score_threshold),
+            FusionMode::Nms => weighted_nms(&results, &weights, self.config.iou_threshold, self.config.skip_score_threshold),
+        })
+    }
+}
+
+/// Class-wise NMS over the results of several models, with the scores scaled by the model weights
+/// relative to their mean. Results with a weight of 0 or less are ignored. Panics unless there is
+/// one weight per result.
+pub fn weighted_nms(results: &[DetectionResult], weights: &[f32], iou_threshold: f32, skip_score_threshold: f32) -> DetectionResult {
+    assert_eq!(results.len(), weights.len(), "one weight per result");
+    let (results, weights): (Vec<&DetectionResult>, Vec<f32>) = results.iter().zip(weights)
+        .filter(|(_, &weight)| weight > 0.0)
+        .unzip();
+    if results.is_empty() {
+        return DetectionResult::default();
+    }
+    let mean_weight = weights.iter().sum::<f32>() / weights.len() as f32;
+    let scaled: Vec<DetectionResult> = results.into_iter().zip(&weights)
+        .map(|(result, &weight)| {
+            let mut result = result.clone();
+            for score in &mut result.scores {
+                *score *= weight / mean_weight;
+            }
+            result
+        })
+        .collect();
+    let all = concat(&scaled);
+    let mut order: Vec<usize> = (0..all.scores.len())
+        .filter(|&i| all.scores[i] >= skip_score_threshold)
+        .collect();
+    sort_by_score(&all, &mut order);
+    select(&all, &nms(&all, &order, iou_threshold, false))
+}
+
+struct Cluster {
+    label_id: i32,
+    // (weighted score, box) of every member
+    boxes: Vec<(f32, Vec<f32>)>,
+    fused: Vec<f32>,
+}
+
+/// Weighted Box Fusion (Solovyev et al.). Boxes of the same label overlapping a fused box by more
+/// than `iou_threshold` join it. A fused box is the score weighted mean of its members, and its
+/// score is their mean, lowered when fewer models than the total weight agree on it. Boxes with a
+/// weighted score of 0 or less are ignored. Panics unless there is one weight per result.
+pub fn weighted_boxes_fusion(results: &[DetectionResult], weights: &[f32], iou_threshold: f32, skip_score_threshold: f32) -> DetectionResult {
+    assert_eq!(results.len(), weights.len(), "one weight per result");
+    let mut candidates = vec![];
+    for (result, &weight) in results.iter().zip(weights) {
+        for i in 0..result.scores.len() {
+            if result.scores[i] >= skip_score_threshold && result.scores[i] * weight > 0.0 {
+                candidates.push((result.scores[i] * weight, result.label_ids[i], &result.boxes[i]));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut clusters: Vec<Cluster> = vec![];
+    for (score, label_id, b) in candidates {
+        let best = clusters.iter_mut()
+            .filter(|c| c.label_id == label_id)
+            .map(|c| (crate::postprocess::iou(&c.fused, b), c))
+            .filter(|(overlap, _)| *overlap > iou_threshold)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        match best {
+            Some((_, cluster)) => {
+                cluster.boxes.push((score, b.clone()));
+                let total: f32 = cluster.boxes.iter().map(|(s, _)| s).sum();
+                cluster.fused = (0..4)
+                    .map(|k| cluster.boxes.iter().map(|(s, b)| s * b[k]).sum::<f32>() / total)
+                    .collect();
+            }
+            None => clusters.push(Cluster { label_id, boxes: vec![(score, b.clone())], fused: b.clone() }),
+        }
+    }
+    let positive_weights: Vec<f32> = weights.iter().copied().filter(|&w| w > 0.0).collect();
+    let total_weight: f32 = positive_weights.iter().sum();
+    let mut fused = DetectionResult { type_: results.first().map_or(DetectionResult::default().type_, |r| r.type_), ..Default::default() };
+    for cluster in &clusters {
+        let mean: f32 = cluster.boxes.iter().map(|(s, _)| s).sum::<f32>() / cluster.boxes.len() as f32;
+        let agreeing = (cluster.boxes.len() as f32).min(positive_weights.len() as f32);
+        fused.scores.push(if total_weight > 0.0 { mean * agreeing / total_weight } else { mean });
+        fused.boxes.push(cluster.fused.clone());
+        fused.label_ids.push(cluster.label_id);
+    }
+    let mut order: Vec<usize> = (0..fused.scores.len()).collect();
+    sort_by_score(&fused, &mut order);
+    select(&fused, &order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detections(boxes: &[[f32; 4]], scores: &[f32]) -> DetectionResult {
+        DetectionResult {
+            boxes: boxes.iter().map(|b| b.to_vec()).collect(),
+            scores: scores.to_vec(),
+            label_ids: vec![0; scores.len()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn wbf_averages_overlapping_boxes_by_score() {
+        let a = detections(&[[0.0, 0.0, 10.0, 10.0]], &[0.9]);
+        let b = detections(&[[2.0, 0.0, 12.0, 10.0]], &[0.3]);
+        let fused = weighted_boxes_fusion(&[a, b], &[1.0, 1.0], 0.5, 0.0);
+        assert_eq!(fused.boxes, vec![vec![0.5, 0.0, 10.5, 10.0]]);
+        assert!((fused.scores[0] - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zero_weights_and_scores_are_ignored() {
+        let a = detections(&[[0.0, 0.0, 10.0, 10.0]], &[0.0]);
+        let b = detections(&[[2.0, 0.0, 12.0, 10.0]], &[0.8]);
+        let fused = weighted_boxes_fusion(&[a.clone(), b.clone()], &[1.0, 0.0], 0.5, 0.0);
+        assert!(fused.scores.is_empty());
+        let fused = weighted_nms(&[a.clone(), b.clone()], &[0.0, 0.0], 0.5, 0.0);
+        assert!(fused.scores.is_empty());
+        let fused = weighted_nms(&[a, b], &[-1.0, 2.0], 0.5, 0.0);
+        assert_eq!(fused.scores, vec![0.8]);
+    }
+
+    #[test]
+    fn wbf_agreement_only_counts_weighted_members() {
+        let a = detections(&[[0.0, 0.0, 10.0, 10.0]], &[0.8]);
+        let b = detections(&[[0.0, 0.0, 10.0, 10.0]], &[0.8]);
+        let fused = weighted_boxes_fusion(&[a.clone(), b.clone(), b.clone()], &[1.0, 1.0, 0.0], 0.5, 0.0);
+        assert_eq!(fused.scores, vec![0.8]);
+        // a disabled member doesn't lower the score of a box both others agree on
+        assert_eq!(weighted_boxes_fusion(&[a, b], &[1.0, 1.0], 0.5, 0.0).scores, fused.scores);
+    }
+
+    #[test]
+    #[should_panic(expected = "one weight per result")]
+    fn fusion_needs_a_weight_per_result() {
+        weighted_boxes_fusion(&[detections(&[], &[])], &[], 0.5, 0.0);
+    }
+}
@@ -94,6 +94,7 @@ impl LitePowerMode {
 #[cfg_attr(any(not(windows), target_env = "gnu"), repr(u32))] // include windows-gnu
 #[cfg_attr(all(windows, not(target_env = "gnu")), repr(i32))] // msvc being *special* again
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ResultType {
     UNKNOWN_RESULT = FD_C_ModelFormat_UNKNOWN_RESULT,
     CLASSIFY = FD_C_ModelFormat_CLASSIFY,
@@ -1,10 +1,32 @@
 use std::str::Utf8Error;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum FastDeployError {
     /// Failed to create a new context.
     InitError,
     PredictError,
+    /// A model file or directory could not be read.
+    IoError(String),
+    /// A deploy config file could not be parsed.
+    ConfigError(String),
+    /// The exported architecture has no matching model wrapper.
+    UnsupportedArch(String),
+    /// No pooled model was returned within the timeout.
+    PoolTimeout,
+    /// A model pool was created without models.
+    EmptyPool,
+    /// The request wasn't answered before its deadline.
+    DeadlineExceeded,
+    /// An embedding doesn't have the size of the ones already stored.
+    DimensionMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// A batch call returned a different number of results than it was given images.
+    BatchSizeMismatch {
+        expected: usize,
+        actual: usize,
+    },
     InvalidUtf8 {
         error_len: Option<usize>,
         valid_up_to: usize,
@@ -27,6 +49,18 @@ impl std::fmt::Display for FastDeployError {
         match self {
             InitError => write!(f, "Failed to create a new whisper context."),
             PredictError => write!(f, "model predict error"),
+            IoError(msg) => write!(f, "io error: {}", msg),
+            ConfigError(msg) => write!(f, "invalid config: {}", msg),
+            UnsupportedArch(arch) => write!(f, "unsupported model arch: {}", arch),
+            PoolTimeout => write!(f, "timed out waiting for a pooled model"),
+            EmptyPool => write!(f, "a model pool needs at least one model"),
+            DeadlineExceeded => write!(f, "request deadline exceeded"),
+            DimensionMismatch { expected, actual } => {
+                write!(f, "embedding dimension mismatch: expected {}, got {}", expected, actual)
+            }
+            BatchSizeMismatch { expected, actual } => {
+                write!(f, "batch result count mismatch: expected {}, got {}", expected, actual)
+            }
             InvalidUtf8 {
                 valid_up_to,
                 error_len: Some(len),
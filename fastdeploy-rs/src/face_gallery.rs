@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use crate::errors::FastDeployError;
+use crate::result::{cosine_similarity, FaceRecognitionResult};
+
+const MAGIC: &[u8; 4] = b"FDFG";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaceMatch {
+    pub identity: String,
+    pub similarity: f32,
+}
+
+/// Enrolled face embeddings searched by cosine similarity. An identity may be enrolled with
+/// several embeddings, its best one counts in a search.
+#[derive(Debug, Clone, Default)]
+pub struct FaceGallery {
+    dim: usize,
+    // embeddings are stored normalized
+    entries: Vec<(String, Vec<f32>)>,
+}
+
+impl FaceGallery {
+    pub fn new() -> FaceGallery {
+        FaceGallery::default()
+    }
+
+    pub fn enroll(&mut self, identity: &str, face: &FaceRecognitionResult) -> Result<(), FastDeployError> {
+        self.check_dim(face.embedding.len())?;
+        self.dim = face.embedding.len();
+        self.entries.push((identity.to_string(), normalize(&face.embedding)));
+        Ok(())
+    }
+
+    /// Removes every embedding of `identity` and returns how many there were.
+    pub fn remove(&mut self, identity: &str) -> usize {
+        let len = self.entries.len();
+        self.entries.retain(|(name, _)| name != identity);
+        len - self.entries.len()
+    }
+
+    pub fn identities(&self) -> Vec<&str> {
+        let mut identities: Vec<&str> = self.entries.iter().map(|(name, _)| name.as_str()).collect();
+        identities.sort();
+        identities.dedup();
+        identities
+    }
+
+    /// Number of enrolled embeddings.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The `k` most similar identities with a similarity of at least `threshold`, best first.
+    pub fn search(&self, face: &FaceRecognitionResult, k: usize, threshold: f32) -> Result<Vec<FaceMatch>, FastDeployError> {
+        if self.entries.is_empty() {
+            return Ok(vec![]);
+        }
+        self.check_dim(face.embedding.len())?;
+        let query = normalize(&face.embedding);
+        let mut matches: Vec<FaceMatch> = vec![];
+        for (identity, embedding) in &self.entries {
+            let similarity = cosine_similarity(&query, embedding);
+            if similarity < threshold {
+                continue;
+            }
+            match matches.iter_mut().find(|m| &m.identity == identity) {
+                Some(m) => m.similarity = m.similarity.max(similarity),
+                None => matches.push(FaceMatch { identity: identity.clone(), similarity }),
+            }
+        }
+        matches.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(Ordering::Equal));
+        matches.truncate(k);
+        Ok(matches)
+    }
+
+    /// The best match, if it reaches `threshold`.
+    pub fn identify(&self, face: &FaceRecognitionResult, threshold: f32) -> Result<Option<FaceMatch>, FastDeployError> {
+        Ok(self.search(face, 1, threshold)?.pop())
+    }
+
+    /// Writes the gallery in a small binary format: a header, then each identity with its embedding.
+    /// The file is written next to `path` and renamed over it, so a failed save keeps the old one.
+    pub fn save(&self, path: &str) -> Result<(), FastDeployError> {
+        let mut buf = Vec::with_capacity(16 + self.entries.len() * (self.dim * 4 + 16));
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&VERSION.to_le_bytes());
+        buf.extend_from_slice(&(self.dim as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for (identity, embedding) in &self.entries {
+            buf.extend_from_slice(&(identity.len() as u32).to_le_bytes());
+            buf.extend_from_slice(identity.as_bytes());
+            for v in embedding {
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        let file_name = Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or("gallery");
+        let tmp = Path::new(path).with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+        let written = fs::write(&tmp, buf).and_then(|_| fs::rename(&tmp, path));
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        written.map_err(|e| FastDeployError::IoError(format!("{}: {}", path, e)))
+    }
+
+    pub fn load(path: &str) -> Result<FaceGallery, FastDeployError> {
+        let buf = fs::read(path).map_err(|e| FastDeployError::IoError(format!("{}: {}", path, e)))?;
+        let invalid = || FastDeployError::IoError(format!("{}: not a face gallery file", path));
+        let mut reader = Reader { buf: &buf, pos: 0 };
+        if reader.take(4).ok_or_else(invalid)? != MAGIC || reader.u32().ok_or_else(invalid)? != VERSION {
+            return Err(invalid());
+        }
+        let dim = reader.u32().ok_or_else(invalid)? as usize;
+        let count = reader.u32().ok_or_else(invalid)? as usize;
+        let mut entries = Vec::with_capacity(count.min(buf.len()));
+        for _ in 0..count {
+            let len = reader.u32().ok_or_else(invalid)? as usize;
+            let identity = std::str::from_utf8(reader.take(len).ok_or_else(invalid)?)?.to_string();
+            let embedding = reader.take(dim * 4).ok_or_else(invalid)?
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            entries.push((identity, embedding));
+        }
+        Ok(FaceGallery { dim, entries })
+    }
+
+    fn check_dim(&self, actual: usize) -> Result<(), FastDeployError> {
+        if !self.entries.is_empty() && actual != self.dim {
+            return Err(FastDeployError::DimensionMismatch { expected: self.dim, actual });
+        }
+        Ok(())
+    }
+}
+
+fn normalize(embedding: &[f32]) -> Vec<f32> {
+    let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return embedding.to_vec();
+    }
+    embedding.iter().map(|x| x / norm).collect()
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.buf.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(embedding: &[f32]) -> FaceRecognitionResult {
+        FaceRecognitionResult::new(embedding.to_vec())
+    }
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("fastdeploy-rs-{}-{}", process::id(), name)).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut gallery = FaceGallery::new();
+        gallery.enroll("alice", &face(&[1.0, 0.0, 0.0])).unwrap();
+        gallery.enroll("bob", &face(&[0.0, 3.0, 4.0])).unwrap();
+        let path = temp_path("gallery.fdfg");
+        gallery.save(&path).unwrap();
+        // saving again replaces the file
+        gallery.save(&path).unwrap();
+        let loaded = FaceGallery::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.dim, 3);
+        assert_eq!(loaded.entries, gallery.entries);
+        assert_eq!(loaded.identities(), vec!["alice", "bob"]);
+    }
+
+    #[test]
+    fn load_rejects_bad_magic() {
+        let path = temp_path("not-a-gallery.fdfg");
+        fs::write(&path, b"XXXX\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00").unwrap();
+        let loaded = FaceGallery::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(loaded, Err(FastDeployError::IoError(message)) if message.contains("not a face gallery file")));
+    }
+
+    #[test]
+    fn search_keeps_the_best_k_above_the_threshold() {
+        let mut gallery = FaceGallery::new();
+        gallery.enroll("alice", &face(&[1.0, 0.0])).unwrap();
+        gallery.enroll("alice", &face(&[0.8, 0.6])).unwrap();
+        gallery.enroll("bob", &face(&[0.6, 0.8])).unwrap();
+        gallery.enroll("carol", &face(&[0.0, 1.0])).unwrap();
+        let query = face(&[2.0, 0.0]);
+
+        let matches = gallery.search(&query, 2, 0.5).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[0].identity.as_str(), matches[0].similarity), ("alice", 1.0));
+        assert_eq!(matches[1].identity, "bob");
+        assert!((matches[1].similarity - 0.6).abs() < 1e-6);
+
+        let matches = gallery.search(&query, 5, 0.7).unwrap();
+        assert_eq!(matches.iter().map(|m| m.identity.as_str()).collect::<Vec<_>>(), vec!["alice"]);
+        assert!(gallery.identify(&face(&[-1.0, 0.0]), 0.1).unwrap().is_none());
+        assert!(matches!(gallery.search(&face(&[1.0]), 1, 0.0), Err(FastDeployError::DimensionMismatch { expected: 2, actual: 1 })));
+    }
+}
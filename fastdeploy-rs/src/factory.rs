@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use crate::config::DeployConfig;
+use crate::enum_variables::ModelFormat;
+use crate::errors::FastDeployError;
+use crate::model::{CascadeRCNN, FasterRCNN, FCOS, GFL, MaskRCNN, PaddleYOLOv5, PaddleYOLOv6, PaddleYOLOv7,
+                   PaddleYOLOv8, PaddleYOLOX, PicoDet, PPYOLO, PPYOLOE, PSSDet, RetinaNet, RTMDet, SSD, TOOD,
+                   TTFNet, YOLOv3};
+use crate::predictor::Predictor;
+use crate::result::DetectionResult;
+use crate::runtime_option::RuntimeOption;
+
+pub type BoxedDetector = Box<dyn Predictor<Output=DetectionResult> + Send>;
+
+/// Builds PaddleDetection models from an exported model directory.
+pub struct ModelFactory;
+
+impl ModelFactory {
+    /// `model_dir` is the output of PaddleDetection `export_model.py`: `model.pdmodel`,
+    /// `model.pdiparams` and `infer_cfg.yml`. The wrapper is chosen by the `arch` field of the config.
+    ///
+    /// PP-YOLOE, PP-YOLO and YOLOv3 are all exported with `arch: YOLO` and nothing else in the config
+    /// tells them apart, so such a directory is rejected with `UnsupportedArch`; use `load_as` instead.
+    pub fn load(model_dir: &str, runtime_option: &RuntimeOption) -> Result<BoxedDetector, FastDeployError> {
+        Self::load_dir(model_dir, None, runtime_option)
+    }
+
+    /// Like `load`, but the wrapper is named by `arch` (e.g. `PPYOLOE`, `YOLOv3`, `CascadeRCNN`)
+    /// instead of the `arch` field of the config.
+    pub fn load_as(model_dir: &str, arch: &str, runtime_option: &RuntimeOption) -> Result<BoxedDetector, FastDeployError> {
+        Self::load_dir(model_dir, Some(arch), runtime_option)
+    }
+
+    fn load_dir(model_dir: &str, arch: Option<&str>, runtime_option: &RuntimeOption) -> Result<BoxedDetector, FastDeployError> {
+        let dir = Path::new(model_dir);
+        let config_file = path_to_string(&dir.join("infer_cfg.yml"))?;
+        let (model_file, param_file) = Self::find_model_files(dir)?;
+        let config = DeployConfig::from_detection_file(&config_file)?;
+        let arch = arch.or(config.arch.as_deref()).unwrap_or_default();
+        Self::create(Self::wrapper_name(arch, config.mask)?, &model_file, &param_file, &config_file, runtime_option)
+    }
+
+    /// Maps the `arch` of an infer_cfg.yml to the wrapper that runs it; wrapper names map to themselves.
+    fn wrapper_name(arch: &str, mask: bool) -> Result<&str, FastDeployError> {
+        match arch {
+            "YOLO" => Err(FastDeployError::UnsupportedArch(
+                "YOLO (ambiguous between PPYOLOE, PPYOLO and YOLOv3, use ModelFactory::load_as)".to_string())),
+            "RCNN" if mask => Ok("MaskRCNN"),
+            "RCNN" => Ok("FasterRCNN"),
+            "YOLOX" => Ok("PaddleYOLOX"),
+            "YOLOv5" => Ok("PaddleYOLOv5"),
+            "YOLOv6" => Ok("PaddleYOLOv6"),
+            "YOLOv7" => Ok("PaddleYOLOv7"),
+            "YOLOv8" => Ok("PaddleYOLOv8"),
+            _ => Ok(arch),
+        }
+    }
+
+    fn create(arch: &str, model_file: &str, param_file: &str, config_file: &str,
+              runtime_option: &RuntimeOption) -> Result<BoxedDetector, FastDeployError> {
+        macro_rules! build {
+            ($model:ident) => {{
+                let mut model = $model::new(model_file, param_file, config_file, runtime_option, ModelFormat::PADDLE);
+                if !model.initialized() {
+                    return Err(FastDeployError::InitError);
+                }
+                Ok(Box::new(model))
+            }};
+        }
+        match arch {
+            "PPYOLOE" => build!(PPYOLOE),
+            "PPYOLO" => build!(PPYOLO),
+            "YOLOv3" => build!(YOLOv3),
+            "PicoDet" => build!(PicoDet),
+            "FasterRCNN" => build!(FasterRCNN),
+            "MaskRCNN" => build!(MaskRCNN),
+            "CascadeRCNN" => build!(CascadeRCNN),
+            "PSSDet" => build!(PSSDet),
+            "SSD" => build!(SSD),
+            "PaddleYOLOX" => build!(PaddleYOLOX),
+            "PaddleYOLOv5" => build!(PaddleYOLOv5),
+            "PaddleYOLOv6" => build!(PaddleYOLOv6),
+            "PaddleYOLOv7" => build!(PaddleYOLOv7),
+            "PaddleYOLOv8" => build!(PaddleYOLOv8),
+            "RTMDet" => build!(RTMDet),
+            "RetinaNet" => build!(RetinaNet),
+            "FCOS" => build!(FCOS),
+            "TTFNet" => build!(TTFNet),
+            "TOOD" => build!(TOOD),
+            "GFL" => build!(GFL),
+            _ => Err(FastDeployError::UnsupportedArch(arch.to_string())),
+        }
+    }
+
+    fn find_model_files(dir: &Path) -> Result<(String, String), FastDeployError> {
+        for stem in ["model", "inference"] {
+            let model_file = dir.join(format!("{}.pdmodel", stem));
+            let param_file = dir.join(format!("{}.pdiparams", stem));
+            if model_file.is_file() && param_file.is_file() {
+                return Ok((path_to_string(&model_file)?, path_to_string(&param_file)?));
+            }
+        }
+        Err(FastDeployError::IoError(format!("no paddle model found in {}", dir.display())))
+    }
+}
+
+fn path_to_string(path: &Path) -> Result<String, FastDeployError> {
+    path.to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| FastDeployError::IoError(format!("non utf-8 path: {}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generic_yolo_arch_is_rejected_as_ambiguous() {
+        match ModelFactory::wrapper_name("YOLO", false) {
+            Err(FastDeployError::UnsupportedArch(msg)) => assert!(msg.contains("load_as")),
+            other => panic!("unexpected: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn config_archs_map_to_wrappers() {
+        assert_eq!(ModelFactory::wrapper_name("RCNN", false).unwrap(), "FasterRCNN");
+        assert_eq!(ModelFactory::wrapper_name("RCNN", true).unwrap(), "MaskRCNN");
+        assert_eq!(ModelFactory::wrapper_name("YOLOv8", false).unwrap(), "PaddleYOLOv8");
+        assert_eq!(ModelFactory::wrapper_name("PicoDet", false).unwrap(), "PicoDet");
+    }
+}
@@ -0,0 +1,18 @@
+use crate::errors::FastDeployError;
+use crate::type_bridge::Mat;
+
+/// Image operations that the FastDeploy C api doesn't export. `Mat` holds a `cv::Mat*`, so an
+/// implementation can use an OpenCV binding on `Mat::ptr`, or round trip through image files.
+pub trait ImageOps {
+    /// (width, height) of the image.
+    fn size(&self, img: &Mat) -> Result<(u32, u32), FastDeployError>;
+
+    fn crop(&self, img: &Mat, x: u32, y: u32, width: u32, height: u32) -> Result<Mat, FastDeployError>;
+}
+
+/// Transforms for test time augmentation, only needed by `ensemble::Ensemble`.
+pub trait ImageTransform: ImageOps {
+    fn flip_horizontal(&self, img: &Mat) -> Result<Mat, FastDeployError>;
+
+    fn resize(&self, img: &Mat, width: u32, height: u32) -> Result<Mat, FastDeployError>;
+}
@@ -5,4 +5,23 @@ pub mod result;
 pub mod type_bridge;
 pub mod visual;
 pub mod errors;
-
+pub mod predictor;
+pub mod factory;
+pub mod config;
+pub mod pool;
+pub mod batcher;
+pub mod postprocess;
+pub mod face_gallery;
+pub mod tracking;
+pub mod image_ops;
+pub mod slicing;
+pub mod ensemble;
+pub mod cascade;
+pub mod benchmark;
+pub mod timing;
+pub mod reload;
+pub mod coco;
+#[cfg(feature = "async")]
+pub mod async_predict;
+#[cfg(feature = "serde")]
+pub mod serde_compact;
@@ -4,54 +4,245 @@
 
 use std::ffi::CString;
 use std::iter::zip;
+use std::sync::Arc;
 
 use fastdeploy_bind::*;
 
+use crate::config::{ConfigOverride, DeployConfig, PreprocessConfig};
 use crate::enum_variables::{ModelFormat, ResultType};
 use crate::errors::FastDeployError;
+use crate::pool::TryClone;
+use crate::postprocess::PostprocessConfig;
+use crate::predictor::Predictor;
 use crate::result::{ClassifyResult, ClassifyResultWrapper, DetectionResult, DetectResultWrapper,
                     OCRResult, OcrResultWrapper, OneDimClassifyResultWrapper, OneDimDetectResult,
                     OneDimOcrResultWrapper, OneDimSegmentationResult, RecognizerResult, SegmentationResult,
                     SegmentationResultWrapper};
 use crate::runtime_option::RuntimeOption;
+use crate::timing::CallTimer;
 use crate::type_bridge::{CstrWrapper, Mat, OneDimArrayCstrWrapper,
-                         OneDimMatWrapper,
+                         OneDimMatRefWrapper,
                          TwoDimArrayCstrWrapper, };
 use crate::type_bridge::common::{c_1_float_to_vec, c_1_int32_to_vec, c_2_int32_to_vec, c_3_int32_to_vec, c_bool_to_bool};
 
+macro_rules! impl_predictor {
+    ($model:ident, $output:ty) => {
+        impl Predictor for $model {
+            type Output = $output;
+
+            fn predict(&self, img: &Mat) -> Result<$output, FastDeployError> {
+                $model::predict(self, img)
+            }
+
+            fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<$output>, FastDeployError> {
+                $model::batch_predict(self, imgs)
+            }
+        }
+    };
+    // models whose inherent `predict` takes the image by value
+    ($model:ident, $output:ty, by_value) => {
+        impl Predictor for $model {
+            type Output = $output;
+
+            fn predict(&self, img: &Mat) -> Result<$output, FastDeployError> {
+                $model::predict_ref(self, img)
+            }
+
+            fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<$output>, FastDeployError> {
+                $model::batch_predict(self, imgs)
+            }
+        }
+    };
+    // models without a batch api
+    ($model:ident, $output:ty, single) => {
+        impl Predictor for $model {
+            type Output = $output;
+
+            fn predict(&self, img: &Mat) -> Result<$output, FastDeployError> {
+                $model::predict(self, img)
+            }
+        }
+    };
+}
+
+macro_rules! impl_try_clone {
+    ($($model:ident),*) => {
+        $(
+            impl TryClone for $model {
+                fn try_clone(&self) -> Result<$model, FastDeployError> {
+                    $model::try_clone(self)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_max_batch_size {
+    ($($model:ident),*) => {
+        $(
+            impl $model {
+                /// Splits larger `batch_predict` inputs into chunks of at most `max_batch_size` images.
+                pub fn set_max_batch_size(&mut self, max_batch_size: Option<usize>) {
+                    self.max_batch_size = max_batch_size;
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_postprocess {
+    ($($model:ident),*) => {
+        $(
+            impl $model {
+                pub fn postprocess(&self) -> &PostprocessConfig {
+                    &self.postprocess
+                }
+
+                pub fn set_postprocess(&mut self, postprocess: PostprocessConfig) {
+                    self.postprocess = postprocess;
+                }
+            }
+        )*
+    };
+}
+
+// FastDeploy only reads the preprocessing from the deploy config, so changing it reloads the
+// model from a rewritten copy of that file. Other settings of the wrapper are kept, and later
+// changes are applied on top of earlier ones.
+macro_rules! impl_preprocess {
+    ($($model:ident),*) => {
+        $(
+            impl $model {
+                /// The deploy config the model currently runs with.
+                pub fn deploy_config(&self) -> Result<DeployConfig, FastDeployError> {
+                    match &self.source.config_override {
+                        Some(config) => DeployConfig::from_override(config),
+                        None => DeployConfig::from_file(&self.source.config_file),
+                    }
+                }
+
+                pub fn set_preprocess(&mut self, preprocess: &PreprocessConfig) -> Result<(), FastDeployError> {
+                    let config = match &self.source.config_override {
+                        Some(config) => config.rewrite(preprocess)?,
+                        None => preprocess.write_config(&self.source.config_file)?,
+                    };
+                    let mut source = self.source.clone();
+                    source.config_override = Some(Arc::new(config));
+                    let mut model = Self::from_source(source);
+                    if !model.initialized() {
+                        return Err(FastDeployError::InitError);
+                    }
+                    std::mem::swap(&mut self.ptr, &mut model.ptr);
+                    std::mem::swap(&mut self.source, &mut model.source);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+/// Runs `batch` on chunks of at most `max_batch_size` images and concatenates the results in order.
+fn predict_in_chunks<T>(images: &[&Mat], max_batch_size: Option<usize>,
+                        mut batch: impl FnMut(&[&Mat]) -> Result<Vec<T>, FastDeployError>) -> Result<Vec<T>, FastDeployError> {
+    match max_batch_size {
+        Some(size) if size > 0 && images.len() > size => {
+            let mut results = Vec::with_capacity(images.len());
+            for chunk in images.chunks(size) {
+                results.extend(batch(chunk)?);
+            }
+            Ok(results)
+        }
+        _ => batch(images),
+    }
+}
+
+/// Files and option a model was created from. The C api can't copy a model, so `try_clone`
+/// loads a new one from the same source.
+#[derive(Clone)]
+struct ModelSource {
+    model_file: String,
+    param_file: String,
+    // config, label or table dict file depending on the model
+    config_file: String,
+    // rewritten config from `set_preprocess`, shared with clones and removed with the last one
+    config_override: Option<Arc<ConfigOverride>>,
+    runtime_option: RuntimeOption,
+    model_format: ModelFormat,
+}
+
+impl ModelSource {
+    fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> ModelSource {
+        ModelSource {
+            model_file: model_file.to_string(),
+            param_file: param_file.to_string(),
+            config_file: config_file.to_string(),
+            config_override: None,
+            runtime_option: runtime_option.clone(),
+            model_format,
+        }
+    }
+
+    /// The config file the model is loaded with.
+    fn config_file(&self) -> &str {
+        self.config_override.as_ref().map_or(&self.config_file, |config| config.path())
+    }
+}
+
 pub struct PaddleClasModel {
     ptr: *mut FD_C_PaddleClasModelWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
 }
 
 impl PaddleClasModel {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> PaddleClasModel {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PaddleClasModel {
-                ptr: FD_C_CreatePaddleClasModelWrapper(CString::new(model_file).unwrap().into_raw(),
-                                                       CString::new(param_file).unwrap().into_raw(),
-                                                       CString::new(config_file).unwrap().into_raw(),
-                                                       runtime_option.ptr,
-                                                       model_format.to_raw())
+                ptr: FD_C_CreatePaddleClasModelWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                       CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                       CString::new(source.config_file()).unwrap().into_raw(),
+                                                       source.runtime_option.ptr,
+                                                       source.model_format.to_raw()),
+                source,
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<ClassifyResult, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleClasModel", 1);
         let c_classify_result = ClassifyResultWrapper::new();
         unsafe {
             let ret = FD_C_PaddleClasModelWrapperPredict(self.ptr, img.ptr, c_classify_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let classify_result = ClassifyResult::from(*c_classify_result.ptr);
-            return Ok(classify_result);
+            return Ok(timer.finish_one(classify_result));
         };
     }
-    pub fn batch_predict(&self, images: &mut Vec<Mat>) -> Result<Vec<ClassifyResult>, FastDeployError> {
+    pub fn batch_predict(&self, images: &[&Mat]) -> Result<Vec<ClassifyResult>, FastDeployError> {
+        predict_in_chunks(images, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, images: &[&Mat]) -> Result<Vec<ClassifyResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleClasModel", images.len());
         let mut result = Vec::with_capacity(images.len());
         let c_one_dim_classify_result = OneDimClassifyResultWrapper::new();
         unsafe {
-            let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
-            let ret = FD_C_PaddleClasModelWrapperBatchPredict(self.ptr, one_dim_image, c_one_dim_classify_result.ptr);
+            let one_dim_image = OneDimMatRefWrapper::from(images);
+            let ret = FD_C_PaddleClasModelWrapperBatchPredict(self.ptr, *one_dim_image.ptr, c_one_dim_classify_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
@@ -60,7 +251,7 @@ impl PaddleClasModel {
                 let t = ClassifyResult::from(*c);
                 result.push(t);
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -81,47 +272,72 @@ impl Drop for PaddleClasModel {
 
 pub struct PPYOLOE {
     ptr: *mut FD_C_PPYOLOEWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PPYOLOE {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PPYOLOE {
-                ptr: FD_C_CreatePPYOLOEWrapper(CString::new(model_file).unwrap().into_raw(),
-                                               CString::new(param_file).unwrap().into_raw(),
-                                               CString::new(config_file).unwrap().into_raw(),
-                                               runtime_option.ptr,
-                                               model_format.to_raw())
+                ptr: FD_C_CreatePPYOLOEWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                               CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                               CString::new(source.config_file()).unwrap().into_raw(),
+                                               source.runtime_option.ptr,
+                                               source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PPYOLOE", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PPYOLOEWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PPYOLOE", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PPYOLOEWrapperBatchPredict(self.ptr, one_dim_image,
+            let ret = FD_C_PPYOLOEWrapperBatchPredict(self.ptr, *one_dim_image.ptr,
                                                       one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -142,47 +358,72 @@ impl Drop for PPYOLOE {
 
 pub struct PicoDet {
     ptr: *mut FD_C_PicoDetWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PicoDet {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption,
                model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PicoDet {
-                ptr: FD_C_CreatePicoDetWrapper(CString::new(model_file).unwrap().into_raw(),
-                                               CString::new(param_file).unwrap().into_raw(),
-                                               CString::new(config_file).unwrap().into_raw(),
-                                               runtime_option.ptr,
-                                               model_format.to_raw())
+                ptr: FD_C_CreatePicoDetWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                               CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                               CString::new(source.config_file()).unwrap().into_raw(),
+                                               source.runtime_option.ptr,
+                                               source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PicoDet", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PicoDetWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PicoDet", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PicoDetWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_PicoDetWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -202,46 +443,71 @@ impl Drop for PicoDet {
 
 pub struct PPYOLO {
     ptr: *mut FD_C_PPYOLOWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PPYOLO {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PPYOLO {
-                ptr: FD_C_CreatePPYOLOWrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              CString::new(config_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreatePPYOLOWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.config_file()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PPYOLO", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PPYOLOWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PPYOLO", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PPYOLOWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_PPYOLOWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -261,46 +527,71 @@ impl Drop for PPYOLO {
 
 pub struct YOLOv3 {
     ptr: *mut FD_C_YOLOv3Wrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl YOLOv3 {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return YOLOv3 {
-                ptr: FD_C_CreateYOLOv3Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              CString::new(config_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreateYOLOv3Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.config_file()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv3", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_YOLOv3WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv3", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_YOLOv3WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_YOLOv3WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -320,46 +611,71 @@ impl Drop for YOLOv3 {
 
 pub struct PaddleYOLOX {
     ptr: *mut FD_C_PaddleYOLOXWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PaddleYOLOX {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PaddleYOLOX {
-                ptr: FD_C_CreatePaddleYOLOXWrapper(CString::new(model_file).unwrap().into_raw(),
-                                                   CString::new(param_file).unwrap().into_raw(),
-                                                   CString::new(config_file).unwrap().into_raw(),
-                                                   runtime_option.ptr,
-                                                   model_format.to_raw())
+                ptr: FD_C_CreatePaddleYOLOXWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                   CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                   CString::new(source.config_file()).unwrap().into_raw(),
+                                                   source.runtime_option.ptr,
+                                                   source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOX", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PaddleYOLOXWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOX", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PaddleYOLOXWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_PaddleYOLOXWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -379,46 +695,71 @@ impl Drop for PaddleYOLOX {
 
 pub struct FasterRCNN {
     ptr: *mut FD_C_FasterRCNNWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl FasterRCNN {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return FasterRCNN {
-                ptr: FD_C_CreateFasterRCNNWrapper(CString::new(model_file).unwrap().into_raw(),
-                                                  CString::new(param_file).unwrap().into_raw(),
-                                                  CString::new(config_file).unwrap().into_raw(),
-                                                  runtime_option.ptr,
-                                                  model_format.to_raw())
+                ptr: FD_C_CreateFasterRCNNWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                  CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                  CString::new(source.config_file()).unwrap().into_raw(),
+                                                  source.runtime_option.ptr,
+                                                  source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("FasterRCNN", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_FasterRCNNWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("FasterRCNN", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_FasterRCNNWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_FasterRCNNWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -438,46 +779,71 @@ impl Drop for FasterRCNN {
 
 pub struct MaskRCNN {
     ptr: *mut FD_C_MaskRCNNWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl MaskRCNN {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return MaskRCNN {
-                ptr: FD_C_CreateMaskRCNNWrapper(CString::new(model_file).unwrap().into_raw(),
-                                                CString::new(param_file).unwrap().into_raw(),
-                                                CString::new(config_file).unwrap().into_raw(),
-                                                runtime_option.ptr,
-                                                model_format.to_raw())
+                ptr: FD_C_CreateMaskRCNNWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                CString::new(source.config_file()).unwrap().into_raw(),
+                                                source.runtime_option.ptr,
+                                                source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("MaskRCNN", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_MaskRCNNWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("MaskRCNN", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_MaskRCNNWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_MaskRCNNWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -497,46 +863,71 @@ impl Drop for MaskRCNN {
 
 pub struct SSD {
     ptr: *mut FD_C_SSDWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl SSD {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return SSD {
-                ptr: FD_C_CreateSSDWrapper(CString::new(model_file).unwrap().into_raw(),
-                                           CString::new(param_file).unwrap().into_raw(),
-                                           CString::new(config_file).unwrap().into_raw(),
-                                           runtime_option.ptr,
-                                           model_format.to_raw())
+                ptr: FD_C_CreateSSDWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                           CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                           CString::new(source.config_file()).unwrap().into_raw(),
+                                           source.runtime_option.ptr,
+                                           source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("SSD", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_SSDWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("SSD", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_SSDWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_SSDWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -556,46 +947,71 @@ impl Drop for SSD {
 
 pub struct PaddleYOLOv5 {
     ptr: *mut FD_C_PaddleYOLOv5Wrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PaddleYOLOv5 {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PaddleYOLOv5 {
-                ptr: FD_C_CreatePaddleYOLOv5Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                                    CString::new(param_file).unwrap().into_raw(),
-                                                    CString::new(config_file).unwrap().into_raw(),
-                                                    runtime_option.ptr,
-                                                    model_format.to_raw())
+                ptr: FD_C_CreatePaddleYOLOv5Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                    CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                    CString::new(source.config_file()).unwrap().into_raw(),
+                                                    source.runtime_option.ptr,
+                                                    source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOv5", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PaddleYOLOv5WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOv5", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PaddleYOLOv5WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_PaddleYOLOv5WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -615,46 +1031,71 @@ impl Drop for PaddleYOLOv5 {
 
 pub struct PaddleYOLOv6 {
     ptr: *mut FD_C_PaddleYOLOv6Wrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PaddleYOLOv6 {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PaddleYOLOv6 {
-                ptr: FD_C_CreatePaddleYOLOv6Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                                    CString::new(param_file).unwrap().into_raw(),
-                                                    CString::new(config_file).unwrap().into_raw(),
-                                                    runtime_option.ptr,
-                                                    model_format.to_raw())
+                ptr: FD_C_CreatePaddleYOLOv6Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                    CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                    CString::new(source.config_file()).unwrap().into_raw(),
+                                                    source.runtime_option.ptr,
+                                                    source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOv6", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PaddleYOLOv6WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOv6", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PaddleYOLOv6WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_PaddleYOLOv6WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -674,46 +1115,71 @@ impl Drop for PaddleYOLOv6 {
 
 pub struct PaddleYOLOv7 {
     ptr: *mut FD_C_PaddleYOLOv7Wrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PaddleYOLOv7 {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PaddleYOLOv7 {
-                ptr: FD_C_CreatePaddleYOLOv7Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                                    CString::new(param_file).unwrap().into_raw(),
-                                                    CString::new(config_file).unwrap().into_raw(),
-                                                    runtime_option.ptr,
-                                                    model_format.to_raw())
+                ptr: FD_C_CreatePaddleYOLOv7Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                    CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                    CString::new(source.config_file()).unwrap().into_raw(),
+                                                    source.runtime_option.ptr,
+                                                    source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOv7", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PaddleYOLOv7WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOv7", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PaddleYOLOv7WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_PaddleYOLOv7WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -733,46 +1199,71 @@ impl Drop for PaddleYOLOv7 {
 
 pub struct PaddleYOLOv8 {
     ptr: *mut FD_C_PaddleYOLOv8Wrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PaddleYOLOv8 {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PaddleYOLOv8 {
-                ptr: FD_C_CreatePaddleYOLOv8Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                                    CString::new(param_file).unwrap().into_raw(),
-                                                    CString::new(config_file).unwrap().into_raw(),
-                                                    runtime_option.ptr,
-                                                    model_format.to_raw())
+                ptr: FD_C_CreatePaddleYOLOv8Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                    CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                    CString::new(source.config_file()).unwrap().into_raw(),
+                                                    source.runtime_option.ptr,
+                                                    source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOv8", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PaddleYOLOv8WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleYOLOv8", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PaddleYOLOv8WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_PaddleYOLOv8WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -793,46 +1284,71 @@ impl Drop for PaddleYOLOv8 {
 //
 pub struct RTMDet {
     ptr: *mut FD_C_RTMDetWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl RTMDet {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return RTMDet {
-                ptr: FD_C_CreateRTMDetWrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              CString::new(config_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreateRTMDetWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.config_file()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("RTMDet", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_RTMDetWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("RTMDet", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_RTMDetWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_RTMDetWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -853,46 +1369,71 @@ impl Drop for RTMDet {
 //
 pub struct CascadeRCNN {
     ptr: *mut FD_C_CascadeRCNNWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl CascadeRCNN {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return CascadeRCNN {
-                ptr: FD_C_CreateCascadeRCNNWrapper(CString::new(model_file).unwrap().into_raw(),
-                                                   CString::new(param_file).unwrap().into_raw(),
-                                                   CString::new(config_file).unwrap().into_raw(),
-                                                   runtime_option.ptr,
-                                                   model_format.to_raw())
+                ptr: FD_C_CreateCascadeRCNNWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                   CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                   CString::new(source.config_file()).unwrap().into_raw(),
+                                                   source.runtime_option.ptr,
+                                                   source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("CascadeRCNN", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_CascadeRCNNWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("CascadeRCNN", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_CascadeRCNNWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_CascadeRCNNWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -912,46 +1453,71 @@ impl Drop for CascadeRCNN {
 
 pub struct PSSDet {
     ptr: *mut FD_C_PSSDetWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl PSSDet {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return PSSDet {
-                ptr: FD_C_CreatePSSDetWrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              CString::new(config_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreatePSSDetWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.config_file()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("PSSDet", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_PSSDetWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PSSDet", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_PSSDetWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_PSSDetWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -971,46 +1537,71 @@ impl Drop for PSSDet {
 
 pub struct RetinaNet {
     ptr: *mut FD_C_RetinaNetWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl RetinaNet {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return RetinaNet {
-                ptr: FD_C_CreateRetinaNetWrapper(CString::new(model_file).unwrap().into_raw(),
-                                                 CString::new(param_file).unwrap().into_raw(),
-                                                 CString::new(config_file).unwrap().into_raw(),
-                                                 runtime_option.ptr,
-                                                 model_format.to_raw())
+                ptr: FD_C_CreateRetinaNetWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                 CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                 CString::new(source.config_file()).unwrap().into_raw(),
+                                                 source.runtime_option.ptr,
+                                                 source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("RetinaNet", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_RetinaNetWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("RetinaNet", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_RetinaNetWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_RetinaNetWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -1030,46 +1621,71 @@ impl Drop for RetinaNet {
 
 pub struct FCOS {
     ptr: *mut FD_C_FCOSWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl FCOS {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return FCOS {
-                ptr: FD_C_CreateFCOSWrapper(CString::new(model_file).unwrap().into_raw(),
-                                            CString::new(param_file).unwrap().into_raw(),
-                                            CString::new(config_file).unwrap().into_raw(),
-                                            runtime_option.ptr,
-                                            model_format.to_raw())
+                ptr: FD_C_CreateFCOSWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                            CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                            CString::new(source.config_file()).unwrap().into_raw(),
+                                            source.runtime_option.ptr,
+                                            source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("FCOS", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_FCOSWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("FCOS", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_FCOSWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_FCOSWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -1090,46 +1706,71 @@ impl Drop for FCOS {
 //
 pub struct TTFNet {
     ptr: *mut FD_C_TTFNetWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl TTFNet {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return TTFNet {
-                ptr: FD_C_CreateTTFNetWrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              CString::new(config_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreateTTFNetWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.config_file()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("TTFNet", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_TTFNetWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("TTFNet", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_TTFNetWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_TTFNetWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -1149,46 +1790,71 @@ impl Drop for TTFNet {
 
 pub struct TOOD {
     ptr: *mut FD_C_TOODWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl TOOD {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return TOOD {
-                ptr: FD_C_CreateTOODWrapper(CString::new(model_file).unwrap().into_raw(),
-                                            CString::new(param_file).unwrap().into_raw(),
-                                            CString::new(config_file).unwrap().into_raw(),
-                                            runtime_option.ptr,
-                                            model_format.to_raw())
+                ptr: FD_C_CreateTOODWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                            CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                            CString::new(source.config_file()).unwrap().into_raw(),
+                                            source.runtime_option.ptr,
+                                            source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("TOOD", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_TOODWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("TOOD", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_TOODWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_TOODWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -1209,46 +1875,71 @@ impl Drop for TOOD {
 //
 pub struct GFL {
     ptr: *mut FD_C_GFLWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl GFL {
     pub fn new(model_file: &str, param_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return GFL {
-                ptr: FD_C_CreateGFLWrapper(CString::new(model_file).unwrap().into_raw(),
-                                           CString::new(param_file).unwrap().into_raw(),
-                                           CString::new(config_file).unwrap().into_raw(),
-                                           runtime_option.ptr,
-                                           model_format.to_raw())
+                ptr: FD_C_CreateGFLWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                           CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                           CString::new(source.config_file()).unwrap().into_raw(),
+                                           source.runtime_option.ptr,
+                                           source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("GFL", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_GFLWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("GFL", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_GFLWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_GFLWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -1269,45 +1960,70 @@ impl Drop for GFL {
 //
 pub struct YOLOv5 {
     ptr: *mut FD_C_YOLOv5Wrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl YOLOv5 {
     pub fn new(model_file: &str, param_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, "", runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return YOLOv5 {
-                ptr: FD_C_CreateYOLOv5Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreateYOLOv5Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv5", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_YOLOv5WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv5", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_YOLOv5WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_YOLOv5WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -1327,36 +2043,55 @@ impl Drop for YOLOv5 {
 
 pub struct YOLOv6 {
     ptr: *mut FD_C_YOLOv6Wrapper,
+    source: ModelSource,
+    postprocess: PostprocessConfig,
 }
 
 impl YOLOv6 {
     pub fn new(model_file: &str, param_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, "", runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return YOLOv6 {
-                ptr: FD_C_CreateYOLOv6Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreateYOLOv6Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
             };
         }
     }
-    pub fn predict(&self, img: &Mat, conf_threshold: f32, nms_threshold: f32) -> Result<DetectionResult, FastDeployError> {
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
+    pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv6", 1);
+        let conf_threshold = self.postprocess.score_threshold.unwrap_or(0.25);
+        let nms_threshold = self.postprocess.nms_iou_threshold.unwrap_or(0.5);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_YOLOv6WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr, conf_threshold, nms_threshold);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    // pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    // pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
     //     unsafe {
     //         let mut result = Vec::with_capacity(imgs.len());
-    //         let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+    //         let one_dim_image = OneDimMatRefWrapper::from(imgs);
     //         let one_dim_detection = OneDimDetectResult::new();
-    //         let ret = FD_C_YOLOv6WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+    //         let ret = FD_C_YOLOv6WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
     //         if !c_bool_to_bool(ret) {
     //             return Err(FastDeployError::PredictError);
     //         }
@@ -1385,45 +2120,70 @@ impl Drop for YOLOv6 {
 
 pub struct YOLOv7 {
     ptr: *mut FD_C_YOLOv7Wrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl YOLOv7 {
     pub fn new(model_file: &str, param_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, "", runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return YOLOv7 {
-                ptr: FD_C_CreateYOLOv7Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreateYOLOv7Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv7", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_YOLOv7WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv7", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_YOLOv7WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_YOLOv7WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -1443,45 +2203,70 @@ impl Drop for YOLOv7 {
 
 pub struct YOLOv8 {
     ptr: *mut FD_C_YOLOv8Wrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
+    postprocess: PostprocessConfig,
 }
 
 impl YOLOv8 {
     pub fn new(model_file: &str, param_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, "", runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return YOLOv8 {
-                ptr: FD_C_CreateYOLOv8Wrapper(CString::new(model_file).unwrap().into_raw(),
-                                              CString::new(param_file).unwrap().into_raw(),
-                                              runtime_option.ptr,
-                                              model_format.to_raw())
+                ptr: FD_C_CreateYOLOv8Wrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                              CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                              source.runtime_option.ptr,
+                                              source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv8", 1);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_YOLOv8WrapperPredict(self.ptr, img.ptr, c_detection_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        predict_in_chunks(imgs, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOv8", imgs.len());
         unsafe {
             let mut result = Vec::with_capacity(imgs.len());
-            let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(imgs);
             let one_dim_detection = OneDimDetectResult::new();
-            let ret = FD_C_YOLOv8WrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+            let ret = FD_C_YOLOv8WrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             for i in 0..(*one_dim_detection.ptr).size {
                 let c = (*one_dim_detection.ptr).data.wrapping_add(i);
                 let t = DetectionResult::from(*c);
-                result.push(t)
+                result.push(self.postprocess.apply(t));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         };
     }
     pub fn initialized(&mut self) -> bool {
@@ -1501,36 +2286,55 @@ impl Drop for YOLOv8 {
 
 pub struct YOLOR {
     ptr: *mut FD_C_YOLORWrapper,
+    source: ModelSource,
+    postprocess: PostprocessConfig,
 }
 
 impl YOLOR {
     pub fn new(model_file: &str, param_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, "", runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return YOLOR {
-                ptr: FD_C_CreateYOLORWrapper(CString::new(model_file).unwrap().into_raw(),
-                                             CString::new(param_file).unwrap().into_raw(),
-                                             runtime_option.ptr,
-                                             model_format.to_raw())
+                ptr: FD_C_CreateYOLORWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                             CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                             source.runtime_option.ptr,
+                                             source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
             };
         }
     }
-    pub fn predict(&self, img: &Mat, conf_threshold: f32, nms_threshold: f32) -> Result<DetectionResult, FastDeployError> {
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
+    pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOR", 1);
+        let conf_threshold = self.postprocess.score_threshold.unwrap_or(0.25);
+        let nms_threshold = self.postprocess.nms_iou_threshold.unwrap_or(0.5);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_YOLORWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr, conf_threshold, nms_threshold);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    // pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    // pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
     //     unsafe {
     //         let mut result = Vec::with_capacity(imgs.len());
-    //         let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+    //         let one_dim_image = OneDimMatRefWrapper::from(imgs);
     //         let one_dim_detection = OneDimDetectResult::new();
-    //         let ret = FD_C_YOLORWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+    //         let ret = FD_C_YOLORWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
     //         if !c_bool_to_bool(ret) {
     //             return Err(FastDeployError::PredictError);
     //         }
@@ -1559,36 +2363,55 @@ impl Drop for YOLOR {
 
 pub struct YOLOX {
     ptr: *mut FD_C_YOLOXWrapper,
+    source: ModelSource,
+    postprocess: PostprocessConfig,
 }
 
 impl YOLOX {
     pub fn new(model_file: &str, param_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, "", runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return YOLOX {
-                ptr: FD_C_CreateYOLOXWrapper(CString::new(model_file).unwrap().into_raw(),
-                                             CString::new(param_file).unwrap().into_raw(),
-                                             runtime_option.ptr,
-                                             model_format.to_raw())
+                ptr: FD_C_CreateYOLOXWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                             CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                             source.runtime_option.ptr,
+                                             source.model_format.to_raw()),
+                source,
+                postprocess: PostprocessConfig::default(),
             };
         }
     }
-    pub fn predict(&self, img: &Mat, conf_threshold: f32, nms_threshold: f32) -> Result<DetectionResult, FastDeployError> {
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.postprocess = self.postprocess;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
+    pub fn predict(&self, img: &Mat) -> Result<DetectionResult, FastDeployError> {
+        let mut timer = CallTimer::start("YOLOX", 1);
+        let conf_threshold = self.postprocess.score_threshold.unwrap_or(0.25);
+        let nms_threshold = self.postprocess.nms_iou_threshold.unwrap_or(0.5);
         unsafe {
             let c_detection_result = DetectResultWrapper::new();
             let ret = FD_C_YOLOXWrapperPredict(self.ptr, img.ptr, c_detection_result.ptr, conf_threshold, nms_threshold);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
             let detection_result = DetectionResult::from(*c_detection_result.ptr);
-            return Ok(detection_result);
+            return Ok(timer.finish_one(self.postprocess.apply(detection_result)));
         }
     }
-    // pub fn batch_predict(&self, imgs: &mut Vec<Mat>) -> Result<Vec<DetectionResult>, FastDeployError> {
+    // pub fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<DetectionResult>, FastDeployError> {
     //     unsafe {
     //         let mut result = Vec::with_capacity(imgs.len());
-    //         let one_dim_image = FD_C_OneDimMat { size: imgs.len(), data: &mut (*imgs.as_mut_ptr()).ptr };
+    //         let one_dim_image = OneDimMatRefWrapper::from(imgs);
     //         let one_dim_detection = OneDimDetectResult::new();
-    //         let ret = FD_C_YOLOXWrapperBatchPredict(self.ptr, one_dim_image, one_dim_detection.ptr);
+    //         let ret = FD_C_YOLOXWrapperBatchPredict(self.ptr, *one_dim_image.ptr, one_dim_detection.ptr);
     //         if !c_bool_to_bool(ret) {
     //             return Err(FastDeployError::PredictError);
     //         }
@@ -1618,49 +2441,70 @@ impl Drop for YOLOX {
 
 pub struct Recognizer {
     pub ptr: *mut FD_C_RecognizerWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
 }
 
 impl Recognizer {
     pub fn new(model_file: &str, param_file: &str, label_path: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, param_file, label_path, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         unsafe {
             return Recognizer {
-                ptr: FD_C_CreateRecognizerWrapper(CString::new(model_file).unwrap().into_raw(),
-                                                  CString::new(param_file).unwrap().into_raw(),
-                                                  CString::new(label_path).unwrap().into_raw(),
-                                                  runtime_option.ptr,
-                                                  model_format.to_raw())
+                ptr: FD_C_CreateRecognizerWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                                  CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                                  CString::new(source.config_file()).unwrap().into_raw(),
+                                                  source.runtime_option.ptr,
+                                                  source.model_format.to_raw()),
+                source,
+                max_batch_size: None,
             };
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn predict(&self, image: &Mat) -> Result<RecognizerResult, FastDeployError> {
+        let mut timer = CallTimer::start("Recognizer", 1);
         unsafe {
             let mut s = CstrWrapper::default();
             let mut score = 0.0f32;
             let ret = FD_C_RecognizerWrapperPredict(self.ptr, image.ptr, s.ptr.as_mut(), &mut score);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
-            return Ok(RecognizerResult::new(String::from(s.to_str()?), score));
+            return Ok(timer.finish_one(RecognizerResult::new(String::from(s.to_str()?), score)));
         }
     }
-    pub unsafe fn batch_predict(&mut self, images: &mut Vec<Mat>) -> Result<Vec<RecognizerResult>, FastDeployError> {
+    pub fn batch_predict(&self, images: &[&Mat]) -> Result<Vec<RecognizerResult>, FastDeployError> {
+        predict_in_chunks(images, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, images: &[&Mat]) -> Result<Vec<RecognizerResult>, FastDeployError> {
+        let mut timer = CallTimer::start("Recognizer", images.len());
         let mut text = OneDimArrayCstrWrapper::default();
         let mut score = &mut FD_C_OneDimArrayFloat { data: std::ptr::null_mut(), size: 0 } as *mut FD_C_OneDimArrayFloat;
         let mut result = Vec::with_capacity(images.len());
         unsafe {
-            // let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
-            let one_dim_image = OneDimMatWrapper::from(images).ptr;
-            let ret = FD_C_RecognizerWrapperBatchPredict(self.ptr, *one_dim_image,
+            let one_dim_image = OneDimMatRefWrapper::from(images);
+            let ret = FD_C_RecognizerWrapperBatchPredict(self.ptr, *one_dim_image.ptr,
                                                          text.ptr.as_mut(),
                                                          score);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
+            for (text, score) in zip::<Vec<String>, Vec<f32>>(text.to_vec(), c_1_float_to_vec(*score)) {
+                result.push(RecognizerResult::new(String::from(text), score));
+            };
         }
-        for (text, score) in zip::<Vec<String>, Vec<f32>>(text.to_vec(), c_1_float_to_vec(*score)) {
-            result.push(RecognizerResult::new(String::from(text), score));
-        };
-        return Ok(result);
+        return Ok(timer.finish_batch(result));
     }
     pub fn initialized(&mut self) -> bool {
         unsafe {
@@ -1678,6 +2522,7 @@ impl Drop for Recognizer {
 
 pub struct Classifier {
     pub ptr: *mut FD_C_ClassifierWrapper,
+    source: ModelSource,
 }
 
 impl Classifier {
@@ -1685,44 +2530,60 @@ impl Classifier {
                params_file: &str,
                runtime_option: &RuntimeOption,
                model_format: ModelFormat) -> Self {
+        Self::from_source(ModelSource::new(model_file, params_file, "", runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         Self {
             ptr: unsafe {
-                FD_C_CreateClassifierWrapper(CString::new(model_file).unwrap().into_raw(),
-                                             CString::new(params_file).unwrap().into_raw(),
-                                             runtime_option.ptr,
-                                             model_format.to_raw())
-            }
+                FD_C_CreateClassifierWrapper(CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                                             CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                                             source.runtime_option.ptr,
+                                             source.model_format.to_raw())
+            },
+            source,
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let model = Self::from_source(self.source.clone());
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
     pub fn initialized(&self) -> bool {
         unsafe {
             FD_C_ClassifierWrapperInitialized(self.ptr) != 0
         }
     }
     pub fn predict(&self, img: Mat) -> Result<(i32, f32), FastDeployError> {
+        let mut timer = CallTimer::start("Classifier", 1);
         let mut cls_label = -1i32;
         let mut cls_score = 0.0f32;
         unsafe {
             let ret = FD_C_ClassifierWrapperPredict(self.ptr, img.ptr, &mut cls_label, &mut cls_score);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
+            timer.finish();
             return Ok((cls_label, cls_score));
         }
     }
 
-    pub fn batch_predict_with_index(&self, images: &mut Vec<Mat>,
+    pub fn batch_predict_with_index(&self, images: &[&Mat],
                                     start_index: usize,
                                     end_index: usize,
     ) -> Result<ClassifyResult, FastDeployError> {
+        let mut timer = CallTimer::start("Classifier", images.len());
         unsafe {
-            let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(images);
             let cls_labels = &mut FD_C_OneDimArrayInt32 { data: std::ptr::null_mut(), size: 0 } as *mut FD_C_OneDimArrayInt32;
             let cls_scores = &mut FD_C_OneDimArrayFloat { data: std::ptr::null_mut(), size: 0 } as *mut FD_C_OneDimArrayFloat;
             let ret = FD_C_ClassifierWrapperBatchPredictWithIndex(self.ptr,
-                                                                  one_dim_image,
+                                                                  *one_dim_image.ptr,
                                                                   cls_labels,
                                                                   cls_scores, start_index, end_index);
+            timer.inference_done();
             let s = FD_C_ClassifyResult {
                 label_ids: *cls_labels,
                 scores: *cls_scores,
@@ -1731,7 +2592,7 @@ impl Classifier {
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
-            return Ok(ClassifyResult::from(s));
+            return Ok(timer.finish_one(ClassifyResult::from(s)));
         }
     }
 }
@@ -1748,21 +2609,36 @@ impl Drop for Classifier {
 
 pub struct DBDetector {
     pub ptr: *mut FD_C_DBDetectorWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
 }
 
 impl DBDetector {
     pub fn new(model_file: &str, params_file: &str, runtime_option: &RuntimeOption, model_format: &ModelFormat) -> DBDetector {
+        Self::from_source(ModelSource::new(model_file, params_file, "", runtime_option, *model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         DBDetector {
             ptr: unsafe {
                 FD_C_CreateDBDetectorWrapper(
-                    CString::new(model_file).unwrap().into_raw(),
-                    CString::new(params_file).unwrap().into_raw(),
-                    runtime_option.ptr,
-                    model_format.to_raw(),
+                    CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                    CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                    source.runtime_option.ptr,
+                    source.model_format.to_raw(),
                 )
-            }
+            },
+            source,
+            max_batch_size: None,
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
 
     pub fn initialized(&self) -> bool {
         unsafe {
@@ -1770,22 +2646,29 @@ impl DBDetector {
         }
     }
     pub fn predict(&self, image: Mat) -> Result<Vec<Vec<i32>>, FastDeployError> {
+        let mut timer = CallTimer::start("DBDetector", 1);
         unsafe {
             let box_result = &mut FD_C_TwoDimArrayInt32 {
                 data: &mut FD_C_OneDimArrayInt32 { data: std::ptr::null_mut(), size: 0 },
                 size: 0,
             };
             let ret = FD_C_DBDetectorWrapperPredict(self.ptr, image.ptr, box_result);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
+            timer.finish();
             return Ok(c_2_int32_to_vec(*box_result));
         }
     }
 
-    pub fn batch_predict(&self, images: &mut Vec<Mat>) -> Result<Vec<Vec<Vec<i32>>>, FastDeployError> {
+    pub fn batch_predict(&self, images: &[&Mat]) -> Result<Vec<Vec<Vec<i32>>>, FastDeployError> {
+        predict_in_chunks(images, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, images: &[&Mat]) -> Result<Vec<Vec<Vec<i32>>>, FastDeployError> {
+        let mut timer = CallTimer::start("DBDetector", images.len());
         unsafe {
-            let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(images);
 
 
             let mut det_results = &mut FD_C_ThreeDimArrayInt32 {
@@ -1798,11 +2681,13 @@ impl DBDetector {
                 },
                 size: 0,
             };
-            let ret = FD_C_DBDetectorWrapperBatchPredict(self.ptr, one_dim_image,
+            let ret = FD_C_DBDetectorWrapperBatchPredict(self.ptr, *one_dim_image.ptr,
                                                          det_results);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
+            timer.finish();
             return Ok(c_3_int32_to_vec(*det_results));
         }
     }
@@ -1819,22 +2704,34 @@ impl Drop for DBDetector {
 
 pub struct StructureV2Table {
     pub ptr: *mut FD_C_StructureV2TableWrapper,
+    source: ModelSource,
 }
 
 impl StructureV2Table {
     pub fn new(model_file: &str, params_file: &str, table_char_dict_path: &str, runtime_option: &RuntimeOption, model_format: &ModelFormat) -> StructureV2Table {
+        Self::from_source(ModelSource::new(model_file, params_file, table_char_dict_path, runtime_option, *model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         StructureV2Table {
             ptr: unsafe {
                 FD_C_CreateStructureV2TableWrapper(
-                    CString::new(model_file).unwrap().into_raw(),
-                    CString::new(params_file).unwrap().into_raw(),
-                    CString::new(table_char_dict_path).unwrap().into_raw(),
-                    runtime_option.ptr,
-                    model_format.to_raw(),
+                    CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                    CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                    CString::new(source.config_file()).unwrap().into_raw(),
+                    source.runtime_option.ptr,
+                    source.model_format.to_raw(),
                 )
-            }
+            },
+            source,
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let model = Self::from_source(self.source.clone());
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
 
     pub fn initialized(&self) -> bool {
         unsafe {
@@ -1842,6 +2739,7 @@ impl StructureV2Table {
         }
     }
     pub fn predict(&self, image: Mat) -> Result<(Vec<Vec<i32>>, Vec<String>), FastDeployError> {
+        let mut timer = CallTimer::start("StructureV2Table", 1);
         unsafe {
             let boxes_result = &mut FD_C_TwoDimArrayInt32 {
                 data: &mut FD_C_OneDimArrayInt32 { data: std::ptr::null_mut(), size: 0 },
@@ -1852,16 +2750,19 @@ impl StructureV2Table {
             let ret = FD_C_StructureV2TableWrapperPredict(self.ptr, image.ptr,
                                                           boxes_result,
                                                           structure_result.ptr.as_mut());
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
+            timer.finish();
             return Ok((c_2_int32_to_vec(*boxes_result), structure_result.to_vec()));
         }
     }
 
-    pub fn batch_predict(&self, images: &mut Vec<Mat>) -> Result<(Vec<Vec<Vec<i32>>>, Vec<Vec<&str>>), FastDeployError> {
+    pub fn batch_predict(&self, images: &[&Mat]) -> Result<(Vec<Vec<Vec<i32>>>, Vec<Vec<&str>>), FastDeployError> {
+        let mut timer = CallTimer::start("StructureV2Table", images.len());
         unsafe {
-            let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(images);
             let mut boxes_results = &mut FD_C_ThreeDimArrayInt32 {
                 data: &mut FD_C_TwoDimArrayInt32 {
                     data: &mut FD_C_OneDimArrayInt32 {
@@ -1874,12 +2775,14 @@ impl StructureV2Table {
             };
 
             let mut structure_results = TwoDimArrayCstrWrapper::default();
-            let ret = FD_C_StructureV2TableWrapperBatchPredict(self.ptr, one_dim_image,
+            let ret = FD_C_StructureV2TableWrapperBatchPredict(self.ptr, *one_dim_image.ptr,
                                                                boxes_results,
                                                                structure_results.ptr.as_mut());
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
+            timer.finish();
             return Ok((c_3_int32_to_vec(*boxes_results), structure_results.to_vec()));
         }
     }
@@ -1895,16 +2798,32 @@ impl Drop for StructureV2Table {
 
 pub struct PPOCRv2 {
     pub ptr: *mut FD_C_PPOCRv2Wrapper,
+    // the pipeline points into the sub models, so it owns them
+    sub_models: (DBDetector, Classifier, Recognizer),
+    max_batch_size: Option<usize>,
 }
 
 impl PPOCRv2 {
-    pub fn new(det_model: &DBDetector, cls_model: &Classifier, rec_model: &Recognizer) -> PPOCRv2 {
+    pub fn new(det_model: DBDetector, cls_model: Classifier, rec_model: Recognizer) -> PPOCRv2 {
         PPOCRv2 {
             ptr: unsafe {
                 FD_C_CreatePPOCRv2Wrapper(det_model.ptr, cls_model.ptr, rec_model.ptr)
-            }
+            },
+            sub_models: (det_model, cls_model, rec_model),
+            max_batch_size: None,
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let det_model = self.sub_models.0.try_clone()?;
+        let cls_model = self.sub_models.1.try_clone()?;
+        let rec_model = self.sub_models.2.try_clone()?;
+        let mut model = PPOCRv2::new(det_model, cls_model, rec_model);
+        model.max_batch_size = self.max_batch_size;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
 
     pub fn initialized(&self) -> bool {
         unsafe {
@@ -1912,22 +2831,33 @@ impl PPOCRv2 {
         }
     }
     pub fn predict(&self, image: Mat) -> Result<OCRResult, FastDeployError> {
+        self.predict_ref(&image)
+    }
+
+    fn predict_ref(&self, image: &Mat) -> Result<OCRResult, FastDeployError> {
+        let mut timer = CallTimer::start("PPOCRv2", 1);
         unsafe {
             let ocr_result = OcrResultWrapper::new();
             let ret = FD_C_PPOCRv2WrapperPredict(self.ptr, image.ptr, ocr_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
-            return Ok(OCRResult::from(*ocr_result.ptr));
+            return Ok(timer.finish_one(OCRResult::from(*ocr_result.ptr)));
         }
     }
 
-    pub fn batch_predict(&self, images: &mut Vec<Mat>) -> Result<Vec<OCRResult>, FastDeployError> {
+    pub fn batch_predict(&self, images: &[&Mat]) -> Result<Vec<OCRResult>, FastDeployError> {
+        predict_in_chunks(images, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, images: &[&Mat]) -> Result<Vec<OCRResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PPOCRv2", images.len());
         unsafe {
-            let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(images);
             let ocr_results = OneDimOcrResultWrapper::default();
-            let ret = FD_C_PPOCRv2WrapperBatchPredict(self.ptr, one_dim_image,
+            let ret = FD_C_PPOCRv2WrapperBatchPredict(self.ptr, *one_dim_image.ptr,
                                                       ocr_results.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
@@ -1935,7 +2865,7 @@ impl PPOCRv2 {
             for i in 0..(*ocr_results.ptr).size {
                 result.push(OCRResult::from(*(*ocr_results.ptr).data.wrapping_add(i)));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         }
     }
 }
@@ -1950,16 +2880,32 @@ impl Drop for PPOCRv2 {
 
 pub struct PPOCRv3 {
     pub ptr: *mut FD_C_PPOCRv3Wrapper,
+    // the pipeline points into the sub models, so it owns them
+    sub_models: (DBDetector, Classifier, Recognizer),
+    max_batch_size: Option<usize>,
 }
 
 impl PPOCRv3 {
-    pub fn new(det_model: &DBDetector, cls_model: &Classifier, rec_model: &Recognizer) -> PPOCRv3 {
+    pub fn new(det_model: DBDetector, cls_model: Classifier, rec_model: Recognizer) -> PPOCRv3 {
         PPOCRv3 {
             ptr: unsafe {
                 FD_C_CreatePPOCRv3Wrapper(det_model.ptr, cls_model.ptr, rec_model.ptr)
-            }
+            },
+            sub_models: (det_model, cls_model, rec_model),
+            max_batch_size: None,
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let det_model = self.sub_models.0.try_clone()?;
+        let cls_model = self.sub_models.1.try_clone()?;
+        let rec_model = self.sub_models.2.try_clone()?;
+        let mut model = PPOCRv3::new(det_model, cls_model, rec_model);
+        model.max_batch_size = self.max_batch_size;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
 
     pub fn initialized(&self) -> bool {
         unsafe {
@@ -1967,22 +2913,29 @@ impl PPOCRv3 {
         }
     }
     pub fn predict(&self, image: &Mat) -> Result<OCRResult, FastDeployError> {
+        let mut timer = CallTimer::start("PPOCRv3", 1);
         unsafe {
             let ocr_result = OcrResultWrapper::new();
             let ret = FD_C_PPOCRv3WrapperPredict(self.ptr, image.ptr, ocr_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
-            return Ok(OCRResult::from(*ocr_result.ptr));
+            return Ok(timer.finish_one(OCRResult::from(*ocr_result.ptr)));
         }
     }
 
-    pub fn batch_predict(&self, images: &mut Vec<Mat>) -> Result<Vec<OCRResult>, FastDeployError> {
+    pub fn batch_predict(&self, images: &[&Mat]) -> Result<Vec<OCRResult>, FastDeployError> {
+        predict_in_chunks(images, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, images: &[&Mat]) -> Result<Vec<OCRResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PPOCRv3", images.len());
         unsafe {
-            let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(images);
             let ocr_results = OneDimOcrResultWrapper::default();
-            let ret = FD_C_PPOCRv3WrapperBatchPredict(self.ptr, one_dim_image,
+            let ret = FD_C_PPOCRv3WrapperBatchPredict(self.ptr, *one_dim_image.ptr,
                                                       ocr_results.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
@@ -1990,7 +2943,7 @@ impl PPOCRv3 {
             for i in 0..(*ocr_results.ptr).size {
                 result.push(OCRResult::from(*(*ocr_results.ptr).data.wrapping_add(i)));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         }
     }
 }
@@ -2005,10 +2958,13 @@ impl Drop for PPOCRv3 {
 
 pub struct PPStructureV2Table {
     pub ptr: *mut FD_C_PPStructureV2TableWrapper,
+    // the pipeline points into the sub models, so it owns them
+    sub_models: (DBDetector, Recognizer, StructureV2Table),
+    max_batch_size: Option<usize>,
 }
 
 impl PPStructureV2Table {
-    pub fn new(det_model: &DBDetector, rec_model: &Recognizer, table_model: StructureV2Table) -> PPStructureV2Table {
+    pub fn new(det_model: DBDetector, rec_model: Recognizer, table_model: StructureV2Table) -> PPStructureV2Table {
         PPStructureV2Table {
             ptr: unsafe {
                 FD_C_CreatePPStructureV2TableWrapper(
@@ -2016,8 +2972,21 @@ impl PPStructureV2Table {
                     rec_model.ptr,
                     table_model.ptr,
                 )
-            }
+            },
+            sub_models: (det_model, rec_model, table_model),
+            max_batch_size: None,
+        }
+    }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let det_model = self.sub_models.0.try_clone()?;
+        let rec_model = self.sub_models.1.try_clone()?;
+        let table_model = self.sub_models.2.try_clone()?;
+        let mut model = PPStructureV2Table::new(det_model, rec_model, table_model);
+        model.max_batch_size = self.max_batch_size;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
         }
+        Ok(model)
     }
 
     pub fn initialized(&self) -> bool {
@@ -2026,22 +2995,33 @@ impl PPStructureV2Table {
         }
     }
     pub fn predict(&self, image: Mat) -> Result<OCRResult, FastDeployError> {
+        self.predict_ref(&image)
+    }
+
+    fn predict_ref(&self, image: &Mat) -> Result<OCRResult, FastDeployError> {
+        let mut timer = CallTimer::start("PPStructureV2Table", 1);
         unsafe {
             let ocr_result = OcrResultWrapper::new();
             let ret = FD_C_PPStructureV2TableWrapperPredict(self.ptr, image.ptr, ocr_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
-            return Ok(OCRResult::from(*ocr_result.ptr));
+            return Ok(timer.finish_one(OCRResult::from(*ocr_result.ptr)));
         }
     }
 
-    pub fn batch_predict(&self, images: &mut Vec<Mat>) -> Result<Vec<OCRResult>, FastDeployError> {
+    pub fn batch_predict(&self, images: &[&Mat]) -> Result<Vec<OCRResult>, FastDeployError> {
+        predict_in_chunks(images, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, images: &[&Mat]) -> Result<Vec<OCRResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PPStructureV2Table", images.len());
         unsafe {
-            let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(images);
             let ocr_results = OneDimOcrResultWrapper::default();
-            let ret = FD_C_PPStructureV2TableWrapperBatchPredict(self.ptr, one_dim_image,
+            let ret = FD_C_PPStructureV2TableWrapperBatchPredict(self.ptr, *one_dim_image.ptr,
                                                                  ocr_results.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
@@ -2049,7 +3029,7 @@ impl PPStructureV2Table {
             for i in 0..(*ocr_results.ptr).size {
                 result.push(OCRResult::from(*(*ocr_results.ptr).data.wrapping_add(i)));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         }
     }
 }
@@ -2065,22 +3045,37 @@ impl Drop for PPStructureV2Table {
 //
 pub struct PaddleSegModel {
     ptr: *mut FD_C_PaddleSegModelWrapper,
+    source: ModelSource,
+    max_batch_size: Option<usize>,
 }
 
 impl PaddleSegModel {
     pub fn new(model_file: &str, params_file: &str, config_file: &str, runtime_option: &RuntimeOption, model_format: ModelFormat) -> PaddleSegModel {
+        Self::from_source(ModelSource::new(model_file, params_file, config_file, runtime_option, model_format))
+    }
+    fn from_source(source: ModelSource) -> Self {
         PaddleSegModel {
             ptr: unsafe {
                 FD_C_CreatePaddleSegModelWrapper(
-                    CString::new(model_file).unwrap().into_raw(),
-                    CString::new(params_file).unwrap().into_raw(),
-                    CString::new(config_file).unwrap().into_raw(),
-                    runtime_option.ptr,
-                    model_format.to_raw(),
+                    CString::new(source.model_file.as_str()).unwrap().into_raw(),
+                    CString::new(source.param_file.as_str()).unwrap().into_raw(),
+                    CString::new(source.config_file()).unwrap().into_raw(),
+                    source.runtime_option.ptr,
+                    source.model_format.to_raw(),
                 )
-            }
+            },
+            source,
+            max_batch_size: None,
         }
     }
+    pub fn try_clone(&self) -> Result<Self, FastDeployError> {
+        let mut model = Self::from_source(self.source.clone());
+        model.max_batch_size = self.max_batch_size;
+        if !model.initialized() {
+            return Err(FastDeployError::InitError);
+        }
+        Ok(model)
+    }
 
     pub fn initialized(&self) -> bool {
         unsafe {
@@ -2088,21 +3083,28 @@ impl PaddleSegModel {
         }
     }
     pub fn predict(&self, image: &Mat) -> Result<SegmentationResult, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleSegModel", 1);
         unsafe {
             let segmentation_result = SegmentationResultWrapper::new();
             let ret = FD_C_PaddleSegModelWrapperPredict(self.ptr, image.ptr, segmentation_result.ptr);
+            timer.inference_done();
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
             }
-            return Ok(SegmentationResult::from(*segmentation_result.ptr));
+            return Ok(timer.finish_one(SegmentationResult::from(*segmentation_result.ptr)));
         }
     }
 
-    pub fn batch_predict(&self, images: &mut Vec<Mat>) -> Result<Vec<SegmentationResult>, FastDeployError> {
+    pub fn batch_predict(&self, images: &[&Mat]) -> Result<Vec<SegmentationResult>, FastDeployError> {
+        predict_in_chunks(images, self.max_batch_size, |chunk| self.batch_predict_chunk(chunk))
+    }
+    fn batch_predict_chunk(&self, images: &[&Mat]) -> Result<Vec<SegmentationResult>, FastDeployError> {
+        let mut timer = CallTimer::start("PaddleSegModel", images.len());
         unsafe {
-            let one_dim_image = FD_C_OneDimMat { size: images.len(), data: &mut (*images.as_mut_ptr()).ptr };
+            let one_dim_image = OneDimMatRefWrapper::from(images);
             let segmentation_results = OneDimSegmentationResult::new();
-            let ret = FD_C_PaddleSegModelWrapperBatchPredict(self.ptr, one_dim_image, segmentation_results.ptr);
+            let ret = FD_C_PaddleSegModelWrapperBatchPredict(self.ptr, *one_dim_image.ptr, segmentation_results.ptr);
+            timer.inference_done();
 
             if !c_bool_to_bool(ret) {
                 return Err(FastDeployError::PredictError);
@@ -2111,7 +3113,7 @@ impl PaddleSegModel {
             for i in 0..(*segmentation_results.ptr).size {
                 result.push(SegmentationResult::from(*(*segmentation_results.ptr).data.wrapping_add(i)));
             }
-            return Ok(result);
+            return Ok(timer.finish_batch(result));
         }
     }
 }
@@ -2124,5 +3126,94 @@ impl Drop for PaddleSegModel {
     }
 }
 
-
-
+impl_predictor!(PaddleClasModel, ClassifyResult);
+impl_predictor!(PPYOLOE, DetectionResult);
+impl_predictor!(PicoDet, DetectionResult);
+impl_predictor!(PPYOLO, DetectionResult);
+impl_predictor!(YOLOv3, DetectionResult);
+impl_predictor!(PaddleYOLOX, DetectionResult);
+impl_predictor!(FasterRCNN, DetectionResult);
+impl_predictor!(MaskRCNN, DetectionResult);
+impl_predictor!(SSD, DetectionResult);
+impl_predictor!(PaddleYOLOv5, DetectionResult);
+impl_predictor!(PaddleYOLOv6, DetectionResult);
+impl_predictor!(PaddleYOLOv7, DetectionResult);
+impl_predictor!(PaddleYOLOv8, DetectionResult);
+impl_predictor!(RTMDet, DetectionResult);
+impl_predictor!(CascadeRCNN, DetectionResult);
+impl_predictor!(PSSDet, DetectionResult);
+impl_predictor!(RetinaNet, DetectionResult);
+impl_predictor!(FCOS, DetectionResult);
+impl_predictor!(TTFNet, DetectionResult);
+impl_predictor!(TOOD, DetectionResult);
+impl_predictor!(GFL, DetectionResult);
+impl_predictor!(YOLOv5, DetectionResult);
+impl_predictor!(YOLOv6, DetectionResult, single);
+impl_predictor!(YOLOv7, DetectionResult);
+impl_predictor!(YOLOv8, DetectionResult);
+impl_predictor!(YOLOR, DetectionResult, single);
+impl_predictor!(YOLOX, DetectionResult, single);
+impl_predictor!(Recognizer, RecognizerResult);
+impl_predictor!(PPOCRv2, OCRResult, by_value);
+impl_predictor!(PPOCRv3, OCRResult);
+impl_predictor!(PPStructureV2Table, OCRResult, by_value);
+impl_predictor!(PaddleSegModel, SegmentationResult);
+
+impl_max_batch_size!(PaddleClasModel, PPYOLOE, PicoDet, PPYOLO, YOLOv3, PaddleYOLOX, FasterRCNN,
+                     MaskRCNN, SSD, PaddleYOLOv5, PaddleYOLOv6, PaddleYOLOv7, PaddleYOLOv8, RTMDet,
+                     CascadeRCNN, PSSDet, RetinaNet, FCOS, TTFNet, TOOD, GFL, YOLOv5, YOLOv7,
+                     YOLOv8, Recognizer, DBDetector, PPOCRv2, PPOCRv3, PPStructureV2Table,
+                     PaddleSegModel);
+
+impl_postprocess!(PPYOLOE, PicoDet, PPYOLO, YOLOv3, PaddleYOLOX, FasterRCNN, MaskRCNN, SSD,
+                  PaddleYOLOv5, PaddleYOLOv6, PaddleYOLOv7, PaddleYOLOv8, RTMDet, CascadeRCNN,
+                  PSSDet, RetinaNet, FCOS, TTFNet, TOOD, GFL, YOLOv5, YOLOv6, YOLOv7, YOLOv8,
+                  YOLOR, YOLOX);
+
+impl_preprocess!(PaddleClasModel, PPYOLOE, PicoDet, PPYOLO, YOLOv3, PaddleYOLOX, FasterRCNN,
+                 MaskRCNN, SSD, PaddleYOLOv5, PaddleYOLOv6, PaddleYOLOv7, PaddleYOLOv8, RTMDet,
+                 CascadeRCNN, PSSDet, RetinaNet, FCOS, TTFNet, TOOD, GFL, PaddleSegModel);
+
+impl_try_clone!(PaddleClasModel, PPYOLOE, PicoDet, PPYOLO, YOLOv3, PaddleYOLOX, FasterRCNN,
+                MaskRCNN, SSD, PaddleYOLOv5, PaddleYOLOv6, PaddleYOLOv7, PaddleYOLOv8, RTMDet,
+                CascadeRCNN, PSSDet, RetinaNet, FCOS, TTFNet, TOOD, GFL, YOLOv5, YOLOv6, YOLOv7,
+                YOLOv8, YOLOR, YOLOX, Recognizer, Classifier, DBDetector, StructureV2Table,
+                PPOCRv2, PPOCRv3, PPStructureV2Table, PaddleSegModel);
+
+// A model can move to another thread, but predict reuses buffers owned by the model, so none of
+// them is Sync. Share one behind a Mutex or give each thread its own `try_clone`.
+unsafe impl Send for PaddleClasModel {}
+unsafe impl Send for PPYOLOE {}
+unsafe impl Send for PicoDet {}
+unsafe impl Send for PPYOLO {}
+unsafe impl Send for YOLOv3 {}
+unsafe impl Send for PaddleYOLOX {}
+unsafe impl Send for FasterRCNN {}
+unsafe impl Send for MaskRCNN {}
+unsafe impl Send for SSD {}
+unsafe impl Send for PaddleYOLOv5 {}
+unsafe impl Send for PaddleYOLOv6 {}
+unsafe impl Send for PaddleYOLOv7 {}
+unsafe impl Send for PaddleYOLOv8 {}
+unsafe impl Send for RTMDet {}
+unsafe impl Send for CascadeRCNN {}
+unsafe impl Send for PSSDet {}
+unsafe impl Send for RetinaNet {}
+unsafe impl Send for FCOS {}
+unsafe impl Send for TTFNet {}
+unsafe impl Send for TOOD {}
+unsafe impl Send for GFL {}
+unsafe impl Send for YOLOv5 {}
+unsafe impl Send for YOLOv6 {}
+unsafe impl Send for YOLOv7 {}
+unsafe impl Send for YOLOv8 {}
+unsafe impl Send for YOLOR {}
+unsafe impl Send for YOLOX {}
+unsafe impl Send for Recognizer {}
+unsafe impl Send for Classifier {}
+unsafe impl Send for DBDetector {}
+unsafe impl Send for StructureV2Table {}
+unsafe impl Send for PPOCRv2 {}
+unsafe impl Send for PPOCRv3 {}
+unsafe impl Send for PPStructureV2Table {}
+unsafe impl Send for PaddleSegModel {}
@@ -0,0 +1,268 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::errors::FastDeployError;
+
+/// Models that can load an independent replica of themselves.
+pub trait TryClone: Sized {
+    fn try_clone(&self) -> Result<Self, FastDeployError>;
+}
+
+/// Snapshot of the pool usage.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PoolMetrics {
+    pub size: usize,
+    pub idle: usize,
+    pub in_use: usize,
+    /// Callers blocked waiting for a model.
+    pub waiting: usize,
+    pub checkouts: u64,
+    pub timeouts: u64,
+    /// Fraction of the replicas currently checked out.
+    pub utilization: f32,
+    /// Total time callers spent waiting for a model.
+    pub total_wait: Duration,
+}
+
+struct PoolState<M> {
+    idle: Vec<M>,
+    waiting: usize,
+    checkouts: u64,
+    timeouts: u64,
+    total_wait: Duration,
+}
+
+/// A fixed set of model replicas handed out to concurrent callers, one caller per replica.
+pub struct ModelPool<M> {
+    size: usize,
+    state: Mutex<PoolState<M>>,
+    returned: Condvar,
+}
+
+impl<M: TryClone> ModelPool<M> {
+    /// Fills the pool with `model` and `size - 1` replicas of it. Fails with `EmptyPool` if `size`
+    /// is 0.
+    pub fn new(model: M, size: usize) -> Result<ModelPool<M>, FastDeployError> {
+        if size == 0 {
+            return Err(FastDeployError::EmptyPool);
+        }
+        let mut models = Vec::with_capacity(size);
+        for _ in 1..size {
+            models.push(model.try_clone()?);
+        }
+        models.push(model);
+        Self::from_models(models)
+    }
+}
+
+impl<M> ModelPool<M> {
+    /// Pools models that were already created, e.g. one per gpu. Fails with `EmptyPool` if
+    /// `models` is empty, since `get` would block forever.
+    pub fn from_models(models: Vec<M>) -> Result<ModelPool<M>, FastDeployError> {
+        if models.is_empty() {
+            return Err(FastDeployError::EmptyPool);
+        }
+        Ok(ModelPool {
+            size: models.len(),
+            state: Mutex::new(PoolState {
+                idle: models,
+                waiting: 0,
+                checkouts: 0,
+                timeouts: 0,
+                total_wait: Duration::ZERO,
+            }),
+            returned: Condvar::new(),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Blocks until a model is free.
+    pub fn get(&self) -> PoolGuard<'_, M> {
+        let start = Instant::now();
+        let mut state = self.lock();
+        state.waiting += 1;
+        while state.idle.is_empty() {
+            state = self.returned.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.waiting -= 1;
+        self.checkout(state, start)
+    }
+
+    /// Like `get`, but gives up with `PoolTimeout` after `timeout`.
+    pub fn get_timeout(&self, timeout: Duration) -> Result<PoolGuard<'_, M>, FastDeployError> {
+        let start = Instant::now();
+        let mut state = self.lock();
+        state.waiting += 1;
+        while state.idle.is_empty() {
+            let remaining = match timeout.checked_sub(start.elapsed()) {
+                Some(remaining) if !remaining.is_zero() => remaining,
+                _ => {
+                    state.waiting -= 1;
+                    state.timeouts += 1;
+                    return Err(FastDeployError::PoolTimeout);
+                }
+            };
+            state = self.returned.wait_timeout(state, remaining).unwrap_or_else(|e| e.into_inner()).0;
+        }
+        state.waiting -= 1;
+        Ok(self.checkout(state, start))
+    }
+
+    /// Returns `None` instead of blocking when every model is in use.
+    pub fn try_get(&self) -> Option<PoolGuard<'_, M>> {
+        let state = self.lock();
+        if state.idle.is_empty() {
+            return None;
+        }
+        Some(self.checkout(state, Instant::now()))
+    }
+
+    pub fn metrics(&self) -> PoolMetrics {
+        let state = self.lock();
+        let in_use = self.size - state.idle.len();
+        PoolMetrics {
+            size: self.size,
+            idle: state.idle.len(),
+            in_use,
+            waiting: state.waiting,
+            checkouts: state.checkouts,
+            timeouts: state.timeouts,
+            utilization: in_use as f32 / self.size as f32,
+            total_wait: state.total_wait,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PoolState<M>> {
+        // a panic while holding the lock can't leave the idle list half updated
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn checkout(&self, mut state: MutexGuard<'_, PoolState<M>>, start: Instant) -> PoolGuard<'_, M> {
+        let model = state.idle.pop().expect("checkout from an empty pool");
+        state.checkouts += 1;
+        state.total_wait += start.elapsed();
+        PoolGuard { pool: self, model: Some(model) }
+    }
+}
+
+/// A checked out model, returned to the pool on drop.
+pub struct PoolGuard<'a, M> {
+    pool: &'a ModelPool<M>,
+    model: Option<M>,
+}
+
+impl<M> Deref for PoolGuard<'_, M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        self.model.as_ref().unwrap()
+    }
+}
+
+impl<M> DerefMut for PoolGuard<'_, M> {
+    fn deref_mut(&mut self) -> &mut M {
+        self.model.as_mut().unwrap()
+    }
+}
+
+impl<M> Drop for PoolGuard<'_, M> {
+    fn drop(&mut self) {
+        if let Some(model) = self.model.take() {
+            self.pool.lock().idle.push(model);
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use super::*;
+
+    struct Replica(usize);
+
+    impl TryClone for Replica {
+        // every replica gets a new id
+        fn try_clone(&self) -> Result<Replica, FastDeployError> {
+            static NEXT_ID: AtomicUsize = AtomicUsize::new(1000);
+            Ok(Replica(NEXT_ID.fetch_add(1, Ordering::Relaxed)))
+        }
+    }
+
+    #[test]
+    fn empty_pools_are_rejected() {
+        assert!(matches!(ModelPool::<Replica>::from_models(vec![]), Err(FastDeployError::EmptyPool)));
+        assert!(matches!(ModelPool::new(Replica(0), 0), Err(FastDeployError::EmptyPool)));
+    }
+
+    #[test]
+    fn new_fills_the_pool_with_replicas() {
+        let pool = ModelPool::new(Replica(0), 3).unwrap();
+        assert_eq!(pool.size(), 3);
+        let guards: Vec<PoolGuard<'_, Replica>> = (0..3).map(|_| pool.try_get().unwrap()).collect();
+        assert!(pool.try_get().is_none());
+        let mut ids: Vec<usize> = guards.iter().map(|guard| guard.0).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.contains(&0));
+    }
+
+    #[test]
+    fn get_returns_the_model_on_drop() {
+        let pool = ModelPool::from_models(vec![Replica(7)]).unwrap();
+        {
+            let model = pool.get();
+            assert_eq!(model.0, 7);
+            assert!(pool.try_get().is_none());
+        }
+        assert_eq!(pool.get().0, 7);
+    }
+
+    #[test]
+    fn get_blocks_until_a_model_is_returned() {
+        let pool = Arc::new(ModelPool::from_models(vec![Replica(1)]).unwrap());
+        let guard = pool.get();
+        let waiter = {
+            let pool = pool.clone();
+            thread::spawn(move || pool.get().0)
+        };
+        while pool.metrics().waiting == 0 {
+            thread::yield_now();
+        }
+        drop(guard);
+        assert_eq!(waiter.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn get_timeout_expires() {
+        let pool = ModelPool::from_models(vec![Replica(1)]).unwrap();
+        let _guard = pool.get();
+        let start = Instant::now();
+        assert!(matches!(pool.get_timeout(Duration::from_millis(20)), Err(FastDeployError::PoolTimeout)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        let metrics = pool.metrics();
+        assert_eq!(metrics.timeouts, 1);
+        assert_eq!(metrics.waiting, 0);
+    }
+
+    #[test]
+    fn metrics_track_usage() {
+        let pool = ModelPool::from_models(vec![Replica(1), Replica(2)]).unwrap();
+        let guard = pool.get();
+        let metrics = pool.metrics();
+        assert_eq!((metrics.size, metrics.idle, metrics.in_use, metrics.checkouts), (2, 1, 1, 1));
+        assert_eq!(metrics.utilization, 0.5);
+        drop(guard);
+        let _guard = pool.get_timeout(Duration::from_millis(10)).unwrap();
+        let metrics = pool.metrics();
+        assert_eq!((metrics.idle, metrics.in_use, metrics.checkouts, metrics.timeouts), (1, 1, 2, 0));
+    }
+}
@@ -0,0 +1,114 @@
+use std::cmp::Ordering;
+
+use crate::result::DetectionResult;
+
+/// Detector postprocessing settings shared by every detection wrapper.
+///
+/// They run on top of the postprocessing inside FastDeploy, so they can only drop boxes.
+/// `YOLOv6`, `YOLOR` and `YOLOX` also pass the thresholds to FastDeploy, defaulting to 0.25/0.5.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct PostprocessConfig {
+    pub score_threshold: Option<f32>,
+    pub nms_iou_threshold: Option<f32>,
+    /// Keeps the highest scoring boxes.
+    pub max_detections: Option<usize>,
+    /// Suppress overlapping boxes across labels instead of per label.
+    /// Only used together with `nms_iou_threshold`; on its own it changes nothing.
+    pub class_agnostic: bool,
+}
+
+impl PostprocessConfig {
+    pub fn apply(&self, result: DetectionResult) -> DetectionResult {
+        if self.score_threshold.is_none() && self.nms_iou_threshold.is_none() && self.max_detections.is_none() {
+            return result;
+        }
+        let mut keep: Vec<usize> = (0..result.scores.len())
+            .filter(|&i| self.score_threshold.map_or(true, |threshold| result.scores[i] >= threshold))
+            .collect();
+        sort_by_score(&result, &mut keep);
+        if let Some(iou_threshold) = self.nms_iou_threshold {
+            keep = nms(&result, &keep, iou_threshold, self.class_agnostic);
+        }
+        if let Some(max_detections) = self.max_detections {
+            keep.truncate(max_detections);
+        }
+        select(&result, &keep)
+    }
+}
+
+/// Intersection over union of two `[x1, y1, x2, y2]` boxes.
+pub fn iou(a: &[f32], b: &[f32]) -> f32 {
+    let w = (a[2].min(b[2]) - a[0].max(b[0])).max(0.0);
+    let h = (a[3].min(b[3]) - a[1].max(b[1])).max(0.0);
+    let inter = w * h;
+    let union = (a[2] - a[0]) * (a[3] - a[1]) + (b[2] - b[0]) * (b[3] - b[1]) - inter;
+    if union <= 0.0 { 0.0 } else { inter / union }
+}
+
+/// Intersection over the area of the smaller box, which stays high for a box and a part of it.
+pub fn intersection_over_smaller(a: &[f32], b: &[f32]) -> f32 {
+    let w = (a[2].min(b[2]) - a[0].max(b[0])).max(0.0);
+    let h = (a[3].min(b[3]) - a[1].max(b[1])).max(0.0);
+    let smaller = ((a[2] - a[0]) * (a[3] - a[1])).min((b[2] - b[0]) * (b[3] - b[1]));
+    if smaller <= 0.0 { 0.0 } else { w * h / smaller }
+}
+
+/// Greedy NMS over the boxes at `indices`, which must be sorted by descending score.
+pub fn nms(result: &DetectionResult, indices: &[usize], iou_threshold: f32, class_agnostic: bool) -> Vec<usize> {
+    let mut keep: Vec<usize> = Vec::with_capacity(indices.len());
+    for &i in indices {
+        let suppressed = keep.iter().any(|&k| {
+            (class_agnostic || result.label_ids[k] == result.label_ids[i])
+                && iou(&result.boxes[k], &result.boxes[i]) > iou_threshold
+        });
+        if !suppressed {
+            keep.push(i);
+        }
+    }
+    keep
+}
+
+pub fn sort_by_score(result: &DetectionResult, indices: &mut [usize]) {
+    indices.sort_by(|&a, &b| result.scores[b].partial_cmp(&result.scores[a]).unwrap_or(Ordering::Equal));
+}
+
+/// Copies the detections at `indices`, in that order.
+pub fn select(result: &DetectionResult, indices: &[usize]) -> DetectionResult {
+    let n = result.scores.len();
+    let mut selected = DetectionResult { type_: result.type_, contain_masks: result.contain_masks, timing: result.timing, ..Default::default() };
+    for &i in indices {
+        selected.boxes.push(result.boxes[i].clone());
+        selected.scores.push(result.scores[i]);
+        selected.label_ids.push(result.label_ids[i]);
+        // rotated boxes and masks are only filled by some models
+        if result.rotated_boxes.len() == n {
+            selected.rotated_boxes.push(result.rotated_boxes[i].clone());
+        }
+        if result.masks.len() == n {
+            selected.masks.push(result.masks[i].clone());
+        }
+    }
+    selected
+}
+
+/// Appends the detections of `results`. Rotated boxes and masks are kept only if every result has
+/// them for each box.
+pub fn concat(results: &[DetectionResult]) -> DetectionResult {
+    let mut all = DetectionResult::default();
+    let with_rotated = results.iter().all(|r| r.rotated_boxes.len() == r.scores.len());
+    let with_masks = results.iter().all(|r| r.masks.len() == r.scores.len());
+    for result in results {
+        all.boxes.extend(result.boxes.iter().cloned());
+        all.scores.extend(&result.scores);
+        all.label_ids.extend(&result.label_ids);
+        if with_rotated {
+            all.rotated_boxes.extend(result.rotated_boxes.iter().cloned());
+        }
+        if with_masks {
+            all.masks.extend(result.masks.iter().cloned());
+        }
+        all.contain_masks |= result.contain_masks;
+        all.type_ = result.type_;
+    }
+    all
+}
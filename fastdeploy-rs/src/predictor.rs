@@ -0,0 +1,14 @@
+use crate::errors::FastDeployError;
+use crate::type_bridge::Mat;
+
+/// Common interface of the model wrappers, so callers can hold any of them behind a `Box<dyn Predictor>`.
+pub trait Predictor {
+    type Output;
+
+    fn predict(&self, img: &Mat) -> Result<Self::Output, FastDeployError>;
+
+    /// Models without a native batch api fall back to predicting the images one by one.
+    fn batch_predict(&self, imgs: &[&Mat]) -> Result<Vec<Self::Output>, FastDeployError> {
+        imgs.iter().map(|img| self.predict(img)).collect()
+    }
+}
@@ -0,0 +1,245 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use crate::errors::FastDeployError;
+
+type Loader<M> = Box<dyn Fn() -> Result<M, FastDeployError> + Send + Sync>;
+type Verifier<M> = Box<dyn Fn(&M) -> Result<(), FastDeployError> + Send + Sync>;
+
+#[derive(Debug, Clone, Default)]
+pub struct ReloadStatus {
+    /// Successful loads, including the first one.
+    pub generation: u64,
+    pub last_reload: Option<SystemTime>,
+    /// Error of the last failed reload, cleared by a successful one.
+    pub last_error: Option<FastDeployError>,
+}
+
+/// A model that can be replaced by a freshly loaded one while it serves requests.
+///
+/// `get` hands out the current model, so calls already running finish on the old one, which is
+/// dropped with its last `Arc`. The model wrappers aren't `Sync`, so `M` is usually a `ModelPool`
+/// or a `Mutex` around a wrapper.
+pub struct Reloadable<M> {
+    current: RwLock<Arc<M>>,
+    loader: Loader<M>,
+    verifier: Option<Verifier<M>>,
+    status: Mutex<ReloadStatus>,
+    reloading: Mutex<()>,
+}
+
+impl<M> Reloadable<M> {
+    /// Loads the first model with `loader`, which is called again by every reload.
+    pub fn new<F>(loader: F) -> Result<Reloadable<M>, FastDeployError>
+        where F: Fn() -> Result<M, FastDeployError> + Send + Sync + 'static {
+        let model = loader()?;
+        Ok(Reloadable {
+            current: RwLock::new(Arc::new(model)),
+            loader: Box::new(loader),
+            verifier: None,
+            status: Mutex::new(ReloadStatus { generation: 1, last_reload: Some(SystemTime::now()), last_error: None }),
+            reloading: Mutex::new(()),
+        })
+    }
+
+    /// Checks a reloaded model before it is swapped in, e.g. by predicting a sample image.
+    pub fn with_verifier<F>(mut self, verifier: F) -> Reloadable<M>
+        where F: Fn(&M) -> Result<(), FastDeployError> + Send + Sync + 'static {
+        self.verifier = Some(Box::new(verifier));
+        self
+    }
+
+    pub fn get(&self) -> Arc<M> {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Loads and verifies a new model, then swaps it in. On failure the current model is kept and
+    /// the error is returned and recorded in the status. Concurrent reloads run one at a time.
+    pub fn reload(&self) -> Result<(), FastDeployError> {
+        let _reloading = self.reloading.lock().unwrap_or_else(|e| e.into_inner());
+        let loaded = (self.loader)().and_then(|model| {
+            if let Some(verifier) = &self.verifier {
+                verifier(&model)?;
+            }
+            Ok(model)
+        });
+        match loaded {
+            Ok(model) => {
+                let old = std::mem::replace(&mut *self.current.write().unwrap_or_else(|e| e.into_inner()), Arc::new(model));
+                // drop the old model outside the write lock
+                drop(old);
+                let mut status = self.lock_status();
+                status.generation += 1;
+                status.last_reload = Some(SystemTime::now());
+                status.last_error = None;
+                Ok(())
+            }
+            Err(e) => {
+                self.lock_status().last_error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    pub fn status(&self) -> ReloadStatus {
+        self.lock_status().clone()
+    }
+
+    fn lock_status(&self) -> MutexGuard<'_, ReloadStatus> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<M: Send + Sync + 'static> Reloadable<M> {
+    /// Polls the modification times of the files under `dir` every `interval` and reloads once
+    /// they changed and then stayed the same for one more poll, so a model being copied isn't
+    /// loaded half written. Failed reloads keep the current model and are retried on the next
+    /// change. Watching stops when the returned `Watcher` is dropped.
+    pub fn watch(self: &Arc<Self>, dir: impl Into<PathBuf>, interval: Duration) -> Result<Watcher, FastDeployError> {
+        let dir = dir.into();
+        let mut seen = latest_modification(&dir)?;
+        let reloadable = self.clone();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let worker = thread::spawn(move || {
+            let mut pending = None;
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                // a file that can't be read now is usually being replaced
+                let Ok(modified) = latest_modification(&dir) else { continue };
+                if modified == seen {
+                    continue;
+                }
+                if pending != Some(modified) {
+                    pending = Some(modified);
+                    continue;
+                }
+                seen = modified;
+                pending = None;
+                let _ = reloadable.reload();
+            }
+        });
+        Ok(Watcher { stop: Some(stop), worker: Some(worker) })
+    }
+}
+
+/// Stops the watching thread on drop.
+pub struct Watcher {
+    stop: Option<Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Latest modification time of the files under `dir`, `None` if it has none.
+fn latest_modification(dir: &Path) -> Result<Option<SystemTime>, FastDeployError> {
+    let entries = fs::read_dir(dir).map_err(|e| FastDeployError::IoError(format!("{}: {}", dir.display(), e)))?;
+    let mut latest = None;
+    for entry in entries {
+        let entry = entry.map_err(|e| FastDeployError::IoError(format!("{}: {}", dir.display(), e)))?;
+        let metadata = entry.metadata().map_err(|e| FastDeployError::IoError(format!("{}: {}", entry.path().display(), e)))?;
+        let modified = if metadata.is_dir() {
+            latest_modification(&entry.path())?
+        } else {
+            metadata.modified().ok()
+        };
+        latest = latest.max(modified);
+    }
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use super::*;
+
+    struct Version(usize);
+
+    /// Loads versions 1, 2, ... and fails while `fail` is set.
+    fn versions(fail: Arc<AtomicBool>) -> Reloadable<Version> {
+        let loads = AtomicUsize::new(0);
+        Reloadable::new(move || {
+            if fail.load(Ordering::SeqCst) {
+                return Err(FastDeployError::InitError);
+            }
+            Ok(Version(loads.fetch_add(1, Ordering::SeqCst) + 1))
+        }).unwrap()
+    }
+
+    #[test]
+    fn reload_swaps_the_model() {
+        let reloadable = versions(Arc::new(AtomicBool::new(false)));
+        let before = reloadable.get();
+        assert_eq!((before.0, reloadable.status().generation), (1, 1));
+        reloadable.reload().unwrap();
+        let status = reloadable.status();
+        assert_eq!((reloadable.get().0, status.generation), (2, 2));
+        assert!(status.last_error.is_none());
+        // calls that got the model before the swap keep the old one
+        assert_eq!(before.0, 1);
+    }
+
+    #[test]
+    fn failed_loads_keep_the_model() {
+        let fail = Arc::new(AtomicBool::new(false));
+        let reloadable = versions(fail.clone());
+        fail.store(true, Ordering::SeqCst);
+        assert!(matches!(reloadable.reload(), Err(FastDeployError::InitError)));
+        let status = reloadable.status();
+        assert_eq!((reloadable.get().0, status.generation), (1, 1));
+        assert!(matches!(status.last_error, Some(FastDeployError::InitError)));
+
+        fail.store(false, Ordering::SeqCst);
+        reloadable.reload().unwrap();
+        assert!(reloadable.status().last_error.is_none());
+    }
+
+    #[test]
+    fn rejected_models_are_not_swapped_in() {
+        let reloadable = versions(Arc::new(AtomicBool::new(false)))
+            .with_verifier(|model| if model.0 % 2 == 0 { Err(FastDeployError::PredictError) } else { Ok(()) });
+        assert!(reloadable.reload().is_err());
+        let status = reloadable.status();
+        assert_eq!((reloadable.get().0, status.generation), (1, 1));
+        assert!(matches!(status.last_error, Some(FastDeployError::PredictError)));
+        reloadable.reload().unwrap();
+        assert_eq!((reloadable.get().0, reloadable.status().generation), (3, 2));
+    }
+
+    #[test]
+    fn watcher_reloads_once_after_a_burst_and_stops_when_dropped() {
+        let dir = std::env::temp_dir().join(format!("fastdeploy-rs-reload-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("model.pdmodel");
+        fs::write(&file, "v1").unwrap();
+        let reloadable = Arc::new(versions(Arc::new(AtomicBool::new(false))));
+        let interval = Duration::from_millis(50);
+        let watcher = reloadable.watch(&dir, interval).unwrap();
+
+        // a copy in progress keeps changing the file, which isn't loaded until it settles
+        for i in 0..60 {
+            fs::write(&file, format!("v2 part {}", i)).unwrap();
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(reloadable.status().generation, 1);
+        thread::sleep(interval * 4);
+        assert_eq!(reloadable.status().generation, 2);
+
+        drop(watcher);
+        fs::write(&file, "v3").unwrap();
+        thread::sleep(interval * 4);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(reloadable.status().generation, 2);
+    }
+}
@@ -5,15 +5,21 @@
 use fastdeploy_bind::*;
 
 use crate::enum_variables::ResultType;
+use crate::timing::Timing;
 use crate::type_bridge::common::*;
 
 type detect_result_t = *mut FD_C_DetectionResult;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassifyResult {
     pub label_ids: Vec<i32>,
     pub scores: Vec<f32>,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_: ResultType,
+    /// Set on the results returned by a model wrapper.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub timing: Option<Timing>,
 }
 
 impl From<FD_C_ClassifyResult> for ClassifyResult {
@@ -22,6 +28,7 @@ impl From<FD_C_ClassifyResult> for ClassifyResult {
             label_ids: c_1_int32_to_vec(value.label_ids),
             scores: c_1_float_to_vec(value.scores),
             type_: ResultType::from(value.type_),
+            timing: None,
         }
     }
 }
@@ -38,12 +45,17 @@ impl Into<FD_C_ClassifyResult> for ClassifyResult {
 
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SegmentationResult {
     pub label_map: Vec<u8>,
     pub score_map: Vec<f32>,
     pub shape: Vec<i64>,
     pub contain_score_map: bool,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_: ResultType,
+    /// Set on the results returned by a model wrapper.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub timing: Option<Timing>,
 }
 
 impl From<FD_C_SegmentationResult> for SegmentationResult {
@@ -54,6 +66,7 @@ impl From<FD_C_SegmentationResult> for SegmentationResult {
             shape: c_1_int64_to_vec(value.shape),
             contain_score_map: c_bool_to_bool(value.contain_score_map),
             type_: ResultType::from(value.type_),
+            timing: None,
         }
     }
 }
@@ -163,9 +176,11 @@ impl Drop for OneDimDetectResult {
 
 
 #[derive(Debug, Clone)]
-struct Mask {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mask {
     pub data: Vec<u8>,
     pub shape: Vec<i64>,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_: ResultType,
 }
 
@@ -234,6 +249,7 @@ pub fn vec_mask_to_fd_c_two_dim_mask(masks: Vec<Mask>) -> FD_C_OneDimMask {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DetectionResult {
     pub boxes: Vec<Vec<f32>>,
     pub rotated_boxes: Vec<Vec<f32>>,
@@ -241,7 +257,11 @@ pub struct DetectionResult {
     pub label_ids: Vec<i32>,
     pub masks: Vec<Mask>,
     pub contain_masks: bool,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_: ResultType,
+    /// Set on the results returned by a model wrapper.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub timing: Option<Timing>,
 }
 
 
@@ -276,6 +296,7 @@ impl From<FD_C_DetectionResult> for DetectionResult {
             masks: fd_c_two_dim_mask_to_vec_mask(value.masks),
             contain_masks: c_bool_to_bool(value.contain_masks),
             type_: ResultType::from(value.type_),
+            timing: None,
         }
     }
 }
@@ -290,6 +311,7 @@ impl Default for DetectionResult {
             masks: vec![],
             contain_masks: false,
             type_: ResultType::UNKNOWN_RESULT,
+            timing: None,
         }
     }
 }
@@ -307,9 +329,12 @@ impl DetectionResult {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecognizerResult {
     pub text: String,
     pub score: f32,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub timing: Option<Timing>,
 }
 
 impl RecognizerResult {
@@ -317,11 +342,215 @@ impl RecognizerResult {
         Self {
             text,
             score,
+            timing: None,
         }
     }
 }
 
+/// Tracked objects of one frame. Box `i` belongs to track `ids[i]`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MOTResult {
+    pub boxes: Vec<Vec<f32>>,
+    pub ids: Vec<i32>,
+    pub scores: Vec<f32>,
+    pub class_ids: Vec<i32>,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_: ResultType,
+}
+
+impl Default for MOTResult {
+    fn default() -> Self {
+        Self {
+            boxes: vec![],
+            ids: vec![],
+            scores: vec![],
+            class_ids: vec![],
+            type_: ResultType::MOT,
+        }
+    }
+}
+
+impl MOTResult {
+    /// The tracks as detections whose label is the index of the box, so the detection visualizers
+    /// can label each box with its track id.
+    pub fn to_detection_result(&self) -> DetectionResult {
+        DetectionResult {
+            boxes: self.boxes.clone(),
+            scores: self.scores.clone(),
+            label_ids: (0..self.boxes.len() as i32).collect(),
+            type_: ResultType::DETECTION,
+            ..Default::default()
+        }
+    }
+}
+
+/// Face embedding. The C api has no face recognition models yet, so it is filled from embeddings
+/// computed elsewhere.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceRecognitionResult {
+    pub embedding: Vec<f32>,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_: ResultType,
+}
+
+impl FaceRecognitionResult {
+    pub fn new(embedding: Vec<f32>) -> Self {
+        Self {
+            embedding,
+            type_: ResultType::FACE_RECOGNITION,
+        }
+    }
+
+    pub fn cosine_similarity(&self, other: &FaceRecognitionResult) -> f32 {
+        cosine_similarity(&self.embedding, &other.embedding)
+    }
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 { 0.0 } else { dot / norm }
+}
+
+/// Faces of one image with `landmarks_per_face` landmarks each, so face `i` owns
+/// `landmarks[i * landmarks_per_face..(i + 1) * landmarks_per_face]`. The C api has no face
+/// detectors yet, so it is filled from detections made elsewhere.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceDetectionResult {
+    pub boxes: Vec<[f32; 4]>,
+    pub landmarks: Vec<[f32; 2]>,
+    pub scores: Vec<f32>,
+    pub landmarks_per_face: usize,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_: ResultType,
+}
+
+impl Default for FaceDetectionResult {
+    fn default() -> Self {
+        Self {
+            boxes: vec![],
+            landmarks: vec![],
+            scores: vec![],
+            landmarks_per_face: 0,
+            type_: ResultType::FACE_DETECTION,
+        }
+    }
+}
+
+impl FaceDetectionResult {
+    pub fn face_landmarks(&self, face: usize) -> &[[f32; 2]] {
+        let n = self.landmarks_per_face;
+        self.landmarks.get(face * n..(face + 1) * n).unwrap_or(&[])
+    }
+}
+
+/// Names of the COCO-17 keypoints, in the order PP-TinyPose outputs them.
+pub const COCO17_KEYPOINTS: [&str; 17] = [
+    "nose", "left_eye", "right_eye", "left_ear", "right_ear", "left_shoulder", "right_shoulder",
+    "left_elbow", "right_elbow", "left_wrist", "right_wrist", "left_hip", "right_hip",
+    "left_knee", "right_knee", "left_ankle", "right_ankle",
+];
+
+/// Bones of the COCO-17 skeleton as pairs of keypoint indices.
+pub const COCO17_SKELETON: [(usize, usize); 19] = [
+    (15, 13), (13, 11), (16, 14), (14, 12), (11, 12), (5, 11), (6, 12), (5, 6), (5, 7), (6, 8),
+    (7, 9), (8, 10), (1, 2), (0, 1), (0, 2), (1, 3), (2, 4), (3, 5), (4, 6),
+];
+
+/// Keypoints of the people in one image, `num_joints` per person, so joint `j` of person `i` is
+/// `keypoints[i * num_joints + j]`. The C api has no keypoint models yet.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyPointDetectionResult {
+    pub keypoints: Vec<[f32; 2]>,
+    pub scores: Vec<f32>,
+    pub num_joints: usize,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_: ResultType,
+}
+
+impl Default for KeyPointDetectionResult {
+    fn default() -> Self {
+        Self {
+            keypoints: vec![],
+            scores: vec![],
+            num_joints: 0,
+            type_: ResultType::KEYPOINT_DETECTION,
+        }
+    }
+}
+
+impl KeyPointDetectionResult {
+    pub fn num_people(&self) -> usize {
+        self.keypoints.len().checked_div(self.num_joints).unwrap_or(0)
+    }
+
+    /// Bones of person `person` whose both keypoints score at least `threshold`, as line segments
+    /// for drawing.
+    pub fn skeleton(&self, person: usize, threshold: f32) -> Vec<([f32; 2], [f32; 2])> {
+        let base = person * self.num_joints;
+        COCO17_SKELETON.iter()
+            .filter(|&&(a, b)| a < self.num_joints && b < self.num_joints)
+            .filter(|&&(a, b)| [a, b].iter().all(|&j| self.scores.get(base + j).is_some_and(|&s| s >= threshold)))
+            .filter_map(|&(a, b)| Some((*self.keypoints.get(base + a)?, *self.keypoints.get(base + b)?)))
+            .collect()
+    }
+}
+
+/// Head pose as euler angles in degrees. The C api has no head pose models yet.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeadPoseResult {
+    /// yaw, pitch, roll
+    pub euler_angles: [f32; 3],
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_: ResultType,
+}
+
+impl HeadPoseResult {
+    pub fn new(yaw: f32, pitch: f32, roll: f32) -> Self {
+        Self {
+            euler_angles: [yaw, pitch, roll],
+            type_: ResultType::HEADPOSE,
+        }
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.euler_angles[0]
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.euler_angles[1]
+    }
+
+    pub fn roll(&self) -> f32 {
+        self.euler_angles[2]
+    }
+}
+
+/// Facial landmarks of one face. The C api has no face alignment models yet.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceAlignmentResult {
+    pub landmarks: Vec<[f32; 2]>,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_: ResultType,
+}
+
+impl FaceAlignmentResult {
+    pub fn new(landmarks: Vec<[f32; 2]>) -> Self {
+        Self {
+            landmarks,
+            type_: ResultType::FACE_ALIGNMENT,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OCRResult {
     pub boxes: Vec<Vec<i32>>,
     pub text: Vec<String>,
@@ -331,7 +560,11 @@ pub struct OCRResult {
     pub table_boxes: Vec<Vec<i32>>,
     pub table_structure: Vec<String>,
     pub table_html: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_: ResultType,
+    /// Set on the results returned by a model wrapper.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub timing: Option<Timing>,
 }
 
 impl From<FD_C_OCRResult> for OCRResult {
@@ -346,6 +579,7 @@ impl From<FD_C_OCRResult> for OCRResult {
             table_structure: c_1_str_to_vec(value.table_structure),
             table_html: cstr_to_string(value.table_html),
             type_: ResultType::from(value.type_),
+            timing: None,
         }
     }
 }
@@ -1,333 +1,381 @@
-use std::ffi::CString;
-use std::mem;
+use std::cell::RefCell;
+use std::ffi::{c_void, CString};
+use std::sync::Arc;
 
 use fastdeploy_bind::*;
 
 use crate::enum_variables::{LitePowerMode, ModelFormat, RKNpu2CoreMask, RKNpu2CpuName};
 
+type Setter = Arc<dyn Fn(*mut FD_C_RuntimeOptionWrapper) + Send + Sync>;
+
 pub struct RuntimeOption {
     pub(crate) ptr: *mut FD_C_RuntimeOptionWrapper,
+    // every setter applied so far, replayed by `clone` since the C api can't copy an option
+    setters: RefCell<Vec<Setter>>,
 }
 
+unsafe impl Send for RuntimeOption {}
+
 impl RuntimeOption {
     pub fn new() -> Self {
         unsafe {
-            return RuntimeOption { ptr: FD_C_CreateRuntimeOptionWrapper() };
+            return RuntimeOption { ptr: FD_C_CreateRuntimeOptionWrapper(), setters: RefCell::new(vec![]) };
         }
     }
+
+    fn apply(&self, setter: impl Fn(*mut FD_C_RuntimeOptionWrapper) + Send + Sync + 'static) {
+        setter(self.ptr);
+        self.setters.borrow_mut().push(Arc::new(setter));
+    }
     pub fn set_model_path(&mut self, model_path: &str, params_path: &str, format: &ModelFormat) {
-        unsafe {
+        let model_path = CString::new(model_path).unwrap();
+        let params_path = CString::new(params_path).unwrap();
+        let format = format.to_raw();
+        self.apply(move |ptr| unsafe {
             FD_C_RuntimeOptionWrapperSetModelPath(
-                self.ptr,
-                CString::new(model_path).unwrap().into_raw(),
-                CString::new(params_path).unwrap().into_raw(),
-                format.to_raw(),
+                ptr,
+                model_path.as_ptr(),
+                params_path.as_ptr(),
+                format,
             );
-        }
+        });
     }
 
 
     pub fn set_model_buffer(&mut self, model_buffer: &[u8], params_buffer: &[u8], format: &ModelFormat) {
-        unsafe {
+        let model_buffer = CString::new(model_buffer).unwrap();
+        let params_buffer = CString::new(params_buffer).unwrap();
+        let format = format.to_raw();
+        self.apply(move |ptr| unsafe {
             FD_C_RuntimeOptionWrapperSetModelBuffer(
-                self.ptr,
-                CString::new(model_buffer).unwrap().into_raw(),
-                CString::new(params_buffer).unwrap().into_raw(),
-                format.to_raw(),
+                ptr,
+                model_buffer.as_ptr(),
+                params_buffer.as_ptr(),
+                format,
             );
-        }
+        });
     }
     pub fn use_cpu(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseCpu(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseCpu(ptr);
+        });
     }
 
     pub fn use_gpu(&mut self, device_id: i32) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseGpu(self.ptr, device_id);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseGpu(ptr, device_id);
+        });
     }
 
     pub fn use_rk_npu2(&mut self, rk_npu_cpu_name: &RKNpu2CpuName, rk_npu_core_mask: &RKNpu2CoreMask) {
-        unsafe {
+        let rk_npu_cpu_name = rk_npu_cpu_name.to_raw();
+        let rk_npu_core_mask = rk_npu_core_mask.to_raw();
+        self.apply(move |ptr| unsafe {
             FD_C_RuntimeOptionWrapperUseRKNPU2(
-                self.ptr,
-                rk_npu_cpu_name.to_raw(),
-                rk_npu_core_mask.to_raw(),
+                ptr,
+                rk_npu_cpu_name,
+                rk_npu_core_mask,
             );
-        }
+        });
     }
     pub fn use_timvx(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseTimVX(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseTimVX(ptr);
+        });
     }
 
     pub fn use_ascend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseAscend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseAscend(ptr);
+        });
     }
     pub fn use_kunlunxin(&mut self, kunlunxin_id: i32, l3_workspace_size: i32,
                          locked: bool, autotune: bool, autotune_file: &str, precision: &str,
                          adaptive_seqlen: bool, enable_multi_stream: bool, gm_default_size: i64) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseKunlunXin(self.ptr,
+        let autotune_file = CString::new(autotune_file).unwrap();
+        let precision = CString::new(precision).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseKunlunXin(ptr,
                                                   kunlunxin_id,
                                                   l3_workspace_size,
                                                   locked as FD_C_Bool,
                                                   autotune as FD_C_Bool,
-                                                  CString::new(autotune_file).unwrap().into_raw(),
-                                                  CString::new(precision).unwrap().into_raw(),
+                                                  autotune_file.as_ptr(),
+                                                  precision.as_ptr(),
                                                   adaptive_seqlen as FD_C_Bool,
                                                   enable_multi_stream as FD_C_Bool,
                                                   gm_default_size);
-        }
+        });
     }
     pub fn use_sophgo(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseSophgo(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseSophgo(ptr);
+        });
     }
 
-    pub fn set_external_stream(&mut self, external_stream: &mut [u8]) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetExternalStream(self.ptr, mem::transmute(external_stream.as_mut_ptr()));
-        }
+    /// # Safety
+    ///
+    /// The stream is kept as a raw pointer and handed again to every clone of this option, so it
+    /// must outlive the option, its clones and every model created from them.
+    pub unsafe fn set_external_stream(&mut self, external_stream: &mut [u8]) {
+        let external_stream = external_stream.as_mut_ptr() as usize;
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetExternalStream(ptr, external_stream as *mut c_void);
+        });
     }
     pub fn set_cpu_thread_num(&mut self, thread_num: i32) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetCpuThreadNum(self.ptr, thread_num);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetCpuThreadNum(ptr, thread_num);
+        });
     }
 
     pub fn set_ort_graph_opt_level(&mut self, level: i32) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetOrtGraphOptLevel(self.ptr, level);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetOrtGraphOptLevel(ptr, level);
+        });
     }
 
     pub fn use_paddle_backend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUsePaddleBackend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUsePaddleBackend(ptr);
+        });
     }
 
     pub fn use_paddle_inference_backend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUsePaddleInferBackend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUsePaddleInferBackend(ptr);
+        });
     }
 
-    pub fn use_ort_backend(&self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseOrtBackend(self.ptr);
-        }
+    pub fn use_ort_backend(&mut self) {
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseOrtBackend(ptr);
+        });
     }
 
     pub fn use_sophgo_backend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseSophgoBackend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseSophgoBackend(ptr);
+        });
     }
 
     pub fn use_trt_backend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseTrtBackend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseTrtBackend(ptr);
+        });
     }
     pub fn use_poros_backend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUsePorosBackend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUsePorosBackend(ptr);
+        });
     }
 
     pub fn use_openvino_backend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseOpenVINOBackend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseOpenVINOBackend(ptr);
+        });
     }
 
     pub fn use_lite_backend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseLiteBackend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseLiteBackend(ptr);
+        });
     }
 
     pub fn use_paddle_lite_backend(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUsePaddleLiteBackend(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUsePaddleLiteBackend(ptr);
+        });
     }
 
     pub fn set_paddle_mkldnn(&mut self, pd_mkldnn: bool) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetPaddleMKLDNN(self.ptr, pd_mkldnn as FD_C_Bool);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetPaddleMKLDNN(ptr, pd_mkldnn as FD_C_Bool);
+        });
     }
 
     pub fn enable_paddle_2_trt(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperEnablePaddleToTrt(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperEnablePaddleToTrt(ptr);
+        });
     }
 
     pub fn delete_paddle_backend_pass(&mut self, delete_pass_name: &str) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperDeletePaddleBackendPass(self.ptr, CString::new(delete_pass_name).unwrap().into_raw());
-        }
+        let delete_pass_name = CString::new(delete_pass_name).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperDeletePaddleBackendPass(ptr, delete_pass_name.as_ptr());
+        });
     }
 
     pub fn enable_paddle_log_info(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperEnablePaddleLogInfo(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperEnablePaddleLogInfo(ptr);
+        });
     }
 
     pub fn disable_paddle_log_info(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperDisablePaddleLogInfo(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperDisablePaddleLogInfo(ptr);
+        });
     }
 
     pub fn set_paddle_mkldnn_cache_size(&mut self, size: i32) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetPaddleMKLDNNCacheSize(self.ptr, size);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetPaddleMKLDNNCacheSize(ptr, size);
+        });
     }
     pub fn set_openvino_device(&mut self, device_name: &str) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetOpenVINODevice(self.ptr, CString::new(device_name).unwrap().into_raw());
-        }
+        let device_name = CString::new(device_name).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetOpenVINODevice(ptr, device_name.as_ptr());
+        });
     }
 
     pub fn set_lite_opt_model_dir(&mut self, optimized_model_dir: &str) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetLiteOptimizedModelDir(self.ptr,
-                                                              CString::new(optimized_model_dir).unwrap().into_raw());
-        }
+        let optimized_model_dir = CString::new(optimized_model_dir).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetLiteOptimizedModelDir(ptr,
+                                                              optimized_model_dir.as_ptr());
+        });
     }
 
     pub fn set_lite_subgraph_partition_path(&mut self, nnadapter_subgraph_partition_config_path: &str) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetLiteSubgraphPartitionPath(self.ptr,
-                                                                  CString::new(nnadapter_subgraph_partition_config_path).unwrap().into_raw());
-        }
+        let nnadapter_subgraph_partition_config_path = CString::new(nnadapter_subgraph_partition_config_path).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetLiteSubgraphPartitionPath(ptr,
+                                                                  nnadapter_subgraph_partition_config_path.as_ptr());
+        });
     }
 
     pub fn set_lite_subgraph_partition_config_buffer(&mut self, nnadapter_subgraph_partition_config_buffer: &[u8]) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetLiteSubgraphPartitionConfigBuffer(self.ptr,
-                                                                          CString::new(nnadapter_subgraph_partition_config_buffer).unwrap().into_raw());
-        }
+        let nnadapter_subgraph_partition_config_buffer = CString::new(nnadapter_subgraph_partition_config_buffer).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetLiteSubgraphPartitionConfigBuffer(ptr,
+                                                                          nnadapter_subgraph_partition_config_buffer.as_ptr());
+        });
     }
 
     pub fn set_lite_context_properties(&mut self, nnadapter_context_properties: &str) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetLiteContextProperties(self.ptr,
-                                                              CString::new(nnadapter_context_properties).unwrap().into_raw());
-        }
+        let nnadapter_context_properties = CString::new(nnadapter_context_properties).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetLiteContextProperties(ptr,
+                                                              nnadapter_context_properties.as_ptr());
+        });
     }
 
     pub fn set_lite_model_cache_dir(&mut self, nnadapter_model_cache_dir: &str) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetLiteModelCacheDir(self.ptr,
-                                                          CString::new(nnadapter_model_cache_dir).unwrap().into_raw());
-        }
+        let nnadapter_model_cache_dir = CString::new(nnadapter_model_cache_dir).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetLiteModelCacheDir(ptr,
+                                                          nnadapter_model_cache_dir.as_ptr());
+        });
     }
     pub fn set_lite_mixed_precision_quantization_config_path(&mut self, nnadapter_mixed_precision_quantization_config_path: &str) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetLiteMixedPrecisionQuantizationConfigPath(self.ptr,
-                                                                                 CString::new(nnadapter_mixed_precision_quantization_config_path).unwrap().into_raw());
-        }
+        let nnadapter_mixed_precision_quantization_config_path = CString::new(nnadapter_mixed_precision_quantization_config_path).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetLiteMixedPrecisionQuantizationConfigPath(ptr,
+                                                                                 nnadapter_mixed_precision_quantization_config_path.as_ptr());
+        });
     }
 
     pub fn enable_lite_fp16(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperEnableLiteFP16(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperEnableLiteFP16(ptr);
+        });
     }
     pub fn disable_lite_fp16(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperDisableLiteFP16(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperDisableLiteFP16(ptr);
+        });
     }
 
     pub fn enable_lite_int8(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperEnableLiteInt8(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperEnableLiteInt8(ptr);
+        });
     }
     pub fn disable_lite_int8(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperDisableLiteInt8(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperDisableLiteInt8(ptr);
+        });
     }
 
     pub fn set_lite_power_mode(&mut self, mode: &LitePowerMode) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetLitePowerMode(self.ptr, mode.to_raw());
-        }
+        let mode = mode.to_raw();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetLitePowerMode(ptr, mode);
+        });
     }
 
     pub fn enable_trt_fp16(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperEnableTrtFP16(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperEnableTrtFP16(ptr);
+        });
     }
     pub fn disable_trt_fp16(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperDisableTrtFP16(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperDisableTrtFP16(ptr);
+        });
     }
 
     pub fn set_trt_cache_file(&mut self, cache_file_path: &str) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetTrtCacheFile(self.ptr,
-                                                     CString::new(cache_file_path).unwrap().into_raw());
-        }
+        let cache_file_path = CString::new(cache_file_path).unwrap();
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetTrtCacheFile(ptr,
+                                                     cache_file_path.as_ptr());
+        });
     }
 
     pub fn enable_pinned_memory(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperEnablePinnedMemory(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperEnablePinnedMemory(ptr);
+        });
     }
 
     pub fn disable_pinned_memory(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperDisablePinnedMemory(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperDisablePinnedMemory(ptr);
+        });
     }
 
     pub fn enable_paddle_trt_collect_shape(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperEnablePaddleTrtCollectShape(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperEnablePaddleTrtCollectShape(ptr);
+        });
     }
 
     pub fn disable_paddle_trt_collect_shape(&mut self) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperDisablePaddleTrtCollectShape(self.ptr);
-        }
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperDisablePaddleTrtCollectShape(ptr);
+        });
     }
 
     pub fn set_openvino_streams(&mut self, num_streams: i32) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperSetOpenVINOStreams(self.ptr,
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperSetOpenVINOStreams(ptr,
                                                         num_streams);
-        }
+        });
     }
 
     pub fn use_ipu(&mut self, device_num: i32, micro_batch_size: i32, enable_pipelining: bool, batches_per_step: i32) {
-        unsafe {
-            FD_C_RuntimeOptionWrapperUseIpu(self.ptr,
+        self.apply(move |ptr| unsafe {
+            FD_C_RuntimeOptionWrapperUseIpu(ptr,
                                             device_num,
                                             micro_batch_size,
                                             enable_pipelining as FD_C_Bool,
                                             batches_per_step);
+        });
+    }
+}
+
+impl Clone for RuntimeOption {
+    fn clone(&self) -> Self {
+        let option = RuntimeOption::new();
+        for setter in self.setters.borrow().iter() {
+            setter(option.ptr);
+            option.setters.borrow_mut().push(setter.clone());
         }
+        option
     }
 }
 
@@ -338,6 +386,3 @@ impl Drop for RuntimeOption {
         }
     }
 }
-
-
-
@@ -0,0 +1,233 @@
+//! Compact encodings for the large maps of the results, for `#[serde(with = "...")]`.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::enum_variables::ResultType;
+use crate::result::SegmentationResult;
+use crate::timing::Timing;
+
+/// `u8` maps with long runs of the same value, e.g. `label_map`, as flat `[value, run length, ...]`
+/// pairs.
+pub mod rle_u8 {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use serde::ser::SerializeSeq;
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut runs: Vec<(u8, u32)> = vec![];
+        for &v in data {
+            match runs.last_mut() {
+                Some((value, count)) if *value == v && *count < u32::MAX => *count += 1,
+                _ => runs.push((v, 1)),
+            }
+        }
+        let mut seq = serializer.serialize_seq(Some(runs.len() * 2))?;
+        for (value, count) in runs {
+            seq.serialize_element(&(value as u32))?;
+            seq.serialize_element(&count)?;
+        }
+        seq.end()
+    }
+
+    /// Largest map decoded when the expected size isn't known.
+    pub const MAX_LEN: usize = 1 << 28;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let runs = Vec::<u32>::deserialize(deserializer)?;
+        decode(&runs, None).map_err(de::Error::custom)
+    }
+
+    /// Expands the runs after checking their total length against `expected_len`, or `MAX_LEN`
+    /// without one, so a forged run length can't allocate more than that.
+    pub(crate) fn decode(runs: &[u32], expected_len: Option<usize>) -> Result<Vec<u8>, String> {
+        if runs.len() % 2 != 0 {
+            return Err("odd number of run length values".to_string());
+        }
+        let len = runs.chunks(2)
+            .try_fold(0usize, |len, run| len.checked_add(run[1] as usize))
+            .ok_or("run lengths overflow")?;
+        match expected_len {
+            Some(expected) if len != expected => return Err(format!("runs cover {} values, expected {}", len, expected)),
+            None if len > MAX_LEN => return Err(format!("runs cover {} values, more than {}", len, MAX_LEN)),
+            _ => {}
+        }
+        let mut data = Vec::with_capacity(len);
+        for run in runs.chunks(2) {
+            let value = u8::try_from(run[0]).map_err(|_| "run value out of the u8 range")?;
+            data.extend(std::iter::repeat(value).take(run[1] as usize));
+        }
+        Ok(data)
+    }
+}
+
+/// `f32` maps, e.g. `score_map`, as base64 of their little endian bytes.
+pub mod base64_f32 {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn serialize<S: Serializer>(data: &[f32], serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        serializer.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let encoded = encoded.trim_end_matches('=').as_bytes();
+        if encoded.len() % 4 == 1 {
+            return Err(de::Error::custom("invalid base64 length"));
+        }
+        let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+        for chunk in encoded.chunks(4) {
+            let mut n = 0u32;
+            for (i, &c) in chunk.iter().enumerate() {
+                let v = ALPHABET.iter().position(|&a| a == c).ok_or_else(|| de::Error::custom("invalid base64"))?;
+                n |= (v as u32) << (18 - 6 * i);
+            }
+            bytes.extend(n.to_be_bytes()[1..chunk.len()].iter());
+        }
+        if bytes.len() % 4 != 0 {
+            return Err(de::Error::custom("base64 length is not a multiple of 4 bytes"));
+        }
+        Ok(bytes.chunks(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+    }
+}
+
+#[derive(Serialize)]
+struct CompactSegmentationRef<'a> {
+    #[serde(with = "rle_u8")]
+    label_map: &'a [u8],
+    #[serde(serialize_with = "base64_f32::serialize")]
+    score_map: &'a [f32],
+    shape: &'a [i64],
+    contain_score_map: bool,
+    #[serde(rename = "type")]
+    type_: ResultType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timing: Option<Timing>,
+}
+
+#[derive(Deserialize)]
+struct CompactSegmentation {
+    // runs, decoded once the shape is known
+    label_map: Vec<u32>,
+    #[serde(with = "base64_f32")]
+    score_map: Vec<f32>,
+    shape: Vec<i64>,
+    contain_score_map: bool,
+    #[serde(rename = "type")]
+    type_: ResultType,
+    #[serde(default)]
+    timing: Option<Timing>,
+}
+
+/// A `SegmentationResult` with the same field names, encoding `label_map` with `rle_u8` and
+/// `score_map` with `base64_f32`.
+#[derive(Debug, Clone)]
+pub struct CompactSegmentationResult(pub SegmentationResult);
+
+impl Serialize for CompactSegmentationResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let result = &self.0;
+        CompactSegmentationRef {
+            label_map: &result.label_map,
+            score_map: &result.score_map,
+            shape: &result.shape,
+            contain_score_map: result.contain_score_map,
+            type_: result.type_,
+            timing: result.timing,
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CompactSegmentationResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let compact = CompactSegmentation::deserialize(deserializer)?;
+        let len = if compact.shape.is_empty() {
+            Some(0)
+        } else {
+            compact.shape.iter().try_fold(1usize, |len, &dim| len.checked_mul(usize::try_from(dim).ok()?))
+        };
+        let len = len.ok_or_else(|| de::Error::custom("invalid shape"))?;
+        let label_map = rle_u8::decode(&compact.label_map, Some(len)).map_err(de::Error::custom)?;
+        Ok(CompactSegmentationResult(SegmentationResult {
+            label_map,
+            score_map: compact.score_map,
+            shape: compact.shape,
+            contain_score_map: compact.contain_score_map,
+            type_: compact.type_,
+            timing: compact.timing,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Labels(#[serde(with = "rle_u8")] Vec<u8>);
+
+    #[test]
+    fn rle_u8_round_trip() {
+        let labels = Labels(vec![0, 0, 0, 1, 1, 0, 2]);
+        let json = serde_json::to_string(&labels).unwrap();
+        assert_eq!(json, "[0,3,1,2,0,1,2,1]");
+        assert_eq!(serde_json::from_str::<Labels>(&json).unwrap().0, labels.0);
+    }
+
+    #[test]
+    fn rle_u8_rejects_bad_runs() {
+        assert!(serde_json::from_str::<Labels>("[0,3,1]").is_err());
+        assert!(serde_json::from_str::<Labels>("[256,1]").is_err());
+    }
+
+    #[test]
+    fn rle_u8_limits_the_decoded_length() {
+        assert!(serde_json::from_str::<Labels>("[0,4294967295]").is_err());
+        assert!(rle_u8::decode(&[0, 3, 1, 2], Some(6)).is_err());
+        assert_eq!(rle_u8::decode(&[0, 3, 1, 2], Some(5)).unwrap(), vec![0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn base64_f32_rejects_a_leftover_character() {
+        #[derive(Deserialize)]
+        struct Scores(#[serde(with = "base64_f32")] Vec<f32>);
+        assert_eq!(serde_json::from_str::<Scores>("\"AADAPw==\"").unwrap().0, vec![1.5]);
+        assert!(serde_json::from_str::<Scores>("\"AADAPwA\"").is_err());
+        assert!(serde_json::from_str::<Scores>("\"AADAP\"").is_err());
+    }
+
+    #[test]
+    fn compact_segmentation_checks_the_label_map_against_the_shape() {
+        let json = r#"{"label_map":[1,4294967295],"score_map":"","shape":[2,2],"contain_score_map":false,"type":"SEGMENTATION"}"#;
+        assert!(serde_json::from_str::<CompactSegmentationResult>(json).is_err());
+        let json = r#"{"label_map":[1,4],"score_map":"","shape":[2,2],"contain_score_map":false,"type":"SEGMENTATION"}"#;
+        assert_eq!(serde_json::from_str::<CompactSegmentationResult>(json).unwrap().0.label_map, vec![1; 4]);
+    }
+
+    #[test]
+    fn compact_segmentation_round_trip() {
+        let result = SegmentationResult {
+            label_map: vec![1, 1, 0, 0],
+            score_map: vec![0.5, 1.0, -2.25, 0.0],
+            shape: vec![2, 2],
+            contain_score_map: true,
+            type_: ResultType::SEGMENTATION,
+            timing: None,
+        };
+        let json = serde_json::to_string(&CompactSegmentationResult(result.clone())).unwrap();
+        let decoded = serde_json::from_str::<CompactSegmentationResult>(&json).unwrap().0;
+        assert_eq!((decoded.label_map, decoded.score_map, decoded.shape), (result.label_map, result.score_map, result.shape));
+    }
+}
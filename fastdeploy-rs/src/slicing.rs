@@ -0,0 +1,256 @@
+use crate::errors::FastDeployError;
+use crate::image_ops::ImageOps;
+use crate::postprocess::{concat, intersection_over_smaller, nms, select, sort_by_score};
+use crate::predictor::Predictor;
+use crate::result::{DetectionResult, Mask};
+use crate::type_bridge::Mat;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Keep the best box of each overlapping group.
+    Nms,
+    /// Grow the best box of each overlapping group to cover the others, for objects cut by a seam.
+    /// Boxes overlap when their intersection over the smaller box passes the threshold, as in
+    /// SAHI, since the part of an object in one tile has a low IoU with the whole of it.
+    Merge,
+}
+
+/// Settings of sliced (tiled) inference.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SliceConfig {
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Fraction of a tile shared with its neighbours.
+    pub overlap_ratio: f32,
+    pub merge_mode: MergeMode,
+    /// IoU threshold for `MergeMode::Nms`, intersection over the smaller box for `MergeMode::Merge`.
+    pub merge_iou_threshold: f32,
+    pub class_agnostic: bool,
+    /// Also predicts on the whole image, for objects larger than a tile.
+    pub full_image_pass: bool,
+    /// Tiles per `batch_predict` call.
+    pub batch_size: usize,
+}
+
+impl Default for SliceConfig {
+    fn default() -> Self {
+        SliceConfig {
+            tile_width: 640,
+            tile_height: 640,
+            overlap_ratio: 0.2,
+            merge_mode: MergeMode::Nms,
+            merge_iou_threshold: 0.5,
+            class_agnostic: false,
+            full_image_pass: false,
+            batch_size: 8,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Overlapping tiles covering the image. The last row and column are moved back to end at the
+/// image border instead of being cut short.
+pub fn tiles(image_width: u32, image_height: u32, config: &SliceConfig) -> Vec<Tile> {
+    let width = config.tile_width.clamp(1, image_width.max(1));
+    let height = config.tile_height.clamp(1, image_height.max(1));
+    let xs = offsets(image_width, width, config.overlap_ratio);
+    let ys = offsets(image_height, height, config.overlap_ratio);
+    let mut tiles = Vec::with_capacity(xs.len() * ys.len());
+    for &y in &ys {
+        for &x in &xs {
+            tiles.push(Tile { x, y, width, height });
+        }
+    }
+    tiles
+}
+
+fn offsets(length: u32, tile: u32, overlap_ratio: f32) -> Vec<u32> {
+    let step = ((tile as f32 * (1.0 - overlap_ratio.clamp(0.0, 0.95))) as u32).max(1);
+    let mut offsets = vec![];
+    let mut offset = 0;
+    loop {
+        if offset + tile >= length {
+            offsets.push(length.saturating_sub(tile));
+            break;
+        }
+        offsets.push(offset);
+        offset += step;
+    }
+    offsets
+}
+
+/// Runs `model` on overlapping tiles of `img`, maps the boxes back to image coordinates and merges
+/// the duplicates along the seams. Fails with `BatchSizeMismatch` if the model returns a
+/// different number of results than tiles.
+pub fn sliced_predict<P, O>(model: &P, image_ops: &O, img: &Mat, config: &SliceConfig) -> Result<DetectionResult, FastDeployError>
+    where P: Predictor<Output=DetectionResult> + ?Sized,
+          O: ImageOps + ?Sized {
+    let (width, height) = image_ops.size(img)?;
+    let tiles = tiles(width, height, config);
+    let mut results = Vec::with_capacity(tiles.len() + 1);
+    for chunk in tiles.chunks(config.batch_size.max(1)) {
+        let crops = chunk.iter()
+            .map(|tile| image_ops.crop(img, tile.x, tile.y, tile.width, tile.height))
+            .collect::<Result<Vec<Mat>, FastDeployError>>()?;
+        let crop_refs: Vec<&Mat> = crops.iter().collect();
+        let tile_results = model.batch_predict(&crop_refs)?;
+        if tile_results.len() != chunk.len() {
+            return Err(FastDeployError::BatchSizeMismatch { expected: chunk.len(), actual: tile_results.len() });
+        }
+        for (tile, result) in chunk.iter().zip(tile_results) {
+            results.push(shift(result, tile));
+        }
+    }
+    if config.full_image_pass {
+        results.push(model.predict(img)?);
+    }
+    Ok(merge_results(&results, config.merge_mode, config.merge_iou_threshold, config.class_agnostic))
+}
+
+/// Moves a tile result to image coordinates. Masks sized like their box move with it, masks
+/// covering the whole tile are cropped to their box first so they do too.
+pub fn shift(mut result: DetectionResult, tile: &Tile) -> DetectionResult {
+    let tile_shape = [tile.height as i64, tile.width as i64];
+    for (mask, b) in result.masks.iter_mut().zip(&result.boxes) {
+        if mask.shape[..] == tile_shape {
+            *mask = crop_to_box(mask, tile.width as usize, b);
+        }
+    }
+    let (dx, dy) = (tile.x as f32, tile.y as f32);
+    for b in result.boxes.iter_mut().chain(result.rotated_boxes.iter_mut()) {
+        for (i, v) in b.iter_mut().enumerate() {
+            *v += if i % 2 == 0 { dx } else { dy };
+        }
+    }
+    result
+}
+
+fn crop_to_box(mask: &Mask, width: usize, b: &[f32]) -> Mask {
+    let height = mask.data.len() / width.max(1);
+    let (x0, y0) = ((b[0].max(0.0) as usize).min(width), (b[1].max(0.0) as usize).min(height));
+    let (x1, y1) = ((b[2].max(0.0) as usize).min(width).max(x0), (b[3].max(0.0) as usize).min(height).max(y0));
+    let mut data = Vec::with_capacity((x1 - x0) * (y1 - y0));
+    for y in y0..y1 {
+        data.extend_from_slice(&mask.data[y * width + x0..y * width + x1]);
+    }
+    Mask { data, shape: vec![(y1 - y0) as i64, (x1 - x0) as i64], type_: mask.type_ }
+}
+
+/// Concatenates the results and removes the overlapping duplicates.
+pub fn merge_results(results: &[DetectionResult], merge_mode: MergeMode, iou_threshold: f32, class_agnostic: bool) -> DetectionResult {
+    let all = concat(results);
+    let mut order: Vec<usize> = (0..all.scores.len()).collect();
+    sort_by_score(&all, &mut order);
+    match merge_mode {
+        MergeMode::Nms => select(&all, &nms(&all, &order, iou_threshold, class_agnostic)),
+        MergeMode::Merge => merge_boxes(&all, &order, iou_threshold, class_agnostic),
+    }
+}
+
+fn merge_boxes(all: &DetectionResult, order: &[usize], threshold: f32, class_agnostic: bool) -> DetectionResult {
+    let mut used = vec![false; all.scores.len()];
+    let mut keep = vec![];
+    let mut merged_boxes = vec![];
+    for (n, &i) in order.iter().enumerate() {
+        if used[i] {
+            continue;
+        }
+        used[i] = true;
+        let mut merged = all.boxes[i].clone();
+        for &j in &order[n + 1..] {
+            if used[j] || (!class_agnostic && all.label_ids[i] != all.label_ids[j]) {
+                continue;
+            }
+            if intersection_over_smaller(&merged, &all.boxes[j]) > threshold {
+                used[j] = true;
+                merged = vec![
+                    merged[0].min(all.boxes[j][0]),
+                    merged[1].min(all.boxes[j][1]),
+                    merged[2].max(all.boxes[j][2]),
+                    merged[3].max(all.boxes[j][3]),
+                ];
+            }
+        }
+        keep.push(i);
+        merged_boxes.push(merged);
+    }
+    let mut result = select(all, &keep);
+    // masks no longer fit the grown boxes
+    if merged_boxes.iter().zip(&result.boxes).any(|(a, b)| a != b) {
+        result.masks.clear();
+        result.contain_masks = false;
+    }
+    result.boxes = merged_boxes;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enum_variables::ResultType;
+
+    use super::*;
+
+    fn detections(boxes: &[[f32; 4]], scores: &[f32]) -> DetectionResult {
+        DetectionResult {
+            boxes: boxes.iter().map(|b| b.to_vec()).collect(),
+            scores: scores.to_vec(),
+            label_ids: vec![0; scores.len()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn a_tile_larger_than_the_image_is_shrunk_to_it() {
+        let config = SliceConfig { tile_width: 640, tile_height: 640, ..Default::default() };
+        assert_eq!(tiles(300, 200, &config), vec![Tile { x: 0, y: 0, width: 300, height: 200 }]);
+    }
+
+    #[test]
+    fn tiles_without_overlap_cover_exact_multiples() {
+        let config = SliceConfig { tile_width: 100, tile_height: 50, overlap_ratio: 0.0, ..Default::default() };
+        let tiles = tiles(300, 100, &config);
+        let origins: Vec<(u32, u32)> = tiles.iter().map(|t| (t.x, t.y)).collect();
+        assert_eq!(origins, vec![(0, 0), (100, 0), (200, 0), (0, 50), (100, 50), (200, 50)]);
+        assert!(tiles.iter().all(|t| (t.width, t.height) == (100, 50)));
+    }
+
+    #[test]
+    fn overlapping_tiles_end_at_the_border() {
+        // steps of 80, the last tile is moved back from 240 to end at 300
+        assert_eq!(offsets(300, 100, 0.2), vec![0, 80, 160, 200]);
+        assert_eq!(offsets(260, 100, 0.2), vec![0, 80, 160]);
+        assert_eq!(offsets(100, 100, 0.2), vec![0]);
+    }
+
+    #[test]
+    fn merge_joins_the_halves_of_an_object_cut_by_a_seam() {
+        // IoU 0.1, but the smaller box lies 20% inside the other
+        let halves = detections(&[[100.0, 0.0, 200.0, 100.0], [180.0, 0.0, 300.0, 100.0]], &[0.9, 0.8]);
+        let merged = merge_results(std::slice::from_ref(&halves), MergeMode::Merge, 0.1, false);
+        assert_eq!(merged.boxes, vec![vec![100.0, 0.0, 300.0, 100.0]]);
+        assert_eq!(merged.scores, vec![0.9]);
+        let kept = merge_results(&[halves], MergeMode::Nms, 0.1, false);
+        assert_eq!(kept.boxes.len(), 2);
+    }
+
+    #[test]
+    fn shift_moves_box_and_tile_sized_masks() {
+        let tile = Tile { x: 100, y: 50, width: 4, height: 3 };
+        let mut result = detections(&[[1.0, 1.0, 3.0, 3.0], [0.0, 0.0, 2.0, 1.0]], &[0.9, 0.8]);
+        result.masks = vec![
+            Mask { data: vec![0, 0, 0, 0, 0, 1, 1, 0, 0, 1, 0, 0], shape: vec![3, 4], type_: ResultType::MASK },
+            Mask { data: vec![1, 1], shape: vec![1, 2], type_: ResultType::MASK },
+        ];
+        let shifted = shift(result, &tile);
+        assert_eq!(shifted.boxes, vec![vec![101.0, 51.0, 103.0, 53.0], vec![100.0, 50.0, 102.0, 51.0]]);
+        assert_eq!((shifted.masks[0].shape.clone(), shifted.masks[0].data.clone()), (vec![2, 2], vec![1, 1, 1, 0]));
+        assert_eq!((shifted.masks[1].shape.clone(), shifted.masks[1].data.clone()), (vec![1, 2], vec![1, 1]));
+    }
+}
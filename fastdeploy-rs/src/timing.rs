@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+/// Durations of a `predict` or `batch_predict` call. FastDeploy preprocesses, runs and
+/// postprocesses the model in one C api call, so its preprocessing and postprocessing count as
+/// `inference`. `postprocess` covers the conversion to Rust and `PostprocessConfig`.
+///
+/// The OCR sub models `DBDetector`, `Classifier` and `StructureV2Table` return plain tuples and
+/// vectors, so their timing is only recorded on the `tracing` span.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timing {
+    /// Images in the call. Every result of a batch carries the timing of the whole batch.
+    pub batch_size: usize,
+    pub inference: Duration,
+    pub postprocess: Duration,
+    pub total: Duration,
+}
+
+/// Results that carry the timing of the call that produced them.
+pub(crate) trait Timed {
+    fn set_timing(&mut self, timing: Timing);
+}
+
+macro_rules! impl_timed {
+    ($($result:ty),*) => {
+        $(
+            impl Timed for $result {
+                fn set_timing(&mut self, timing: Timing) {
+                    self.timing = Some(timing);
+                }
+            }
+        )*
+    };
+}
+
+impl_timed!(crate::result::ClassifyResult, crate::result::DetectionResult, crate::result::SegmentationResult,
+            crate::result::OCRResult, crate::result::RecognizerResult);
+
+/// Times a model call, inside a `predict` span when the `tracing` feature is on.
+pub(crate) struct CallTimer {
+    batch_size: usize,
+    start: Instant,
+    inference: Option<Duration>,
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+}
+
+impl CallTimer {
+    // the model name is only recorded on the span
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn start(model: &'static str, batch_size: usize) -> CallTimer {
+        CallTimer {
+            batch_size,
+            start: Instant::now(),
+            inference: None,
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!("predict", model, batch_size,
+                                      inference_us = tracing::field::Empty,
+                                      postprocess_us = tracing::field::Empty).entered(),
+        }
+    }
+
+    /// Marks the end of the FastDeploy call.
+    pub(crate) fn inference_done(&mut self) {
+        self.inference = Some(self.start.elapsed());
+    }
+
+    pub(crate) fn finish(self) -> Timing {
+        let total = self.start.elapsed();
+        let inference = self.inference.unwrap_or(total);
+        let timing = Timing { batch_size: self.batch_size, inference, postprocess: total - inference, total };
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("inference_us", timing.inference.as_micros() as u64);
+            self.span.record("postprocess_us", timing.postprocess.as_micros() as u64);
+        }
+        timing
+    }
+
+    pub(crate) fn finish_one<T: Timed>(self, mut result: T) -> T {
+        result.set_timing(self.finish());
+        result
+    }
+
+    pub(crate) fn finish_batch<T: Timed>(self, mut results: Vec<T>) -> Vec<T> {
+        let timing = self.finish();
+        for result in &mut results {
+            result.set_timing(timing);
+        }
+        results
+    }
+}
@@ -0,0 +1,424 @@
+use crate::postprocess::iou;
+use crate::result::{DetectionResult, MOTResult};
+
+/// Settings of the ByteTrack style `Tracker`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TrackerConfig {
+    /// Detections at or above this score are matched first.
+    pub high_score_threshold: f32,
+    /// Detections below this score are ignored. The ones between the two thresholds can only
+    /// keep an existing track alive, e.g. a partly occluded object.
+    pub low_score_threshold: f32,
+    /// Unmatched detections at or above this score start a new track.
+    pub new_track_threshold: f32,
+    /// Minimum IoU with the predicted box to match a high score detection.
+    pub match_iou_threshold: f32,
+    /// Minimum IoU with the predicted box to match a low score detection.
+    pub low_match_iou_threshold: f32,
+    /// Frames a track survives without a match.
+    pub max_lost_frames: usize,
+    /// Consecutive matches before a new track gets an id and is reported.
+    pub confirm_frames: usize,
+    /// Only match detections with the label of the track.
+    pub per_class: bool,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        TrackerConfig {
+            high_score_threshold: 0.5,
+            low_score_threshold: 0.1,
+            new_track_threshold: 0.6,
+            match_iou_threshold: 0.2,
+            low_match_iou_threshold: 0.5,
+            max_lost_frames: 30,
+            confirm_frames: 2,
+            per_class: false,
+        }
+    }
+}
+
+struct Track {
+    // confirmed tracks only
+    id: Option<i32>,
+    label_id: i32,
+    score: f32,
+    kalman: KalmanBoxFilter,
+    hits: usize,
+    lost_frames: usize,
+}
+
+impl Track {
+    fn confirmed(&self) -> bool {
+        self.id.is_some()
+    }
+}
+
+/// Assigns persistent ids to the detections of consecutive frames, using a Kalman filter for
+/// motion and greedy IoU association in two rounds: high score detections first, then low score
+/// ones against the tracks that are still unmatched.
+pub struct Tracker {
+    config: TrackerConfig,
+    tracks: Vec<Track>,
+    next_id: i32,
+}
+
+impl Tracker {
+    pub fn new(config: TrackerConfig) -> Tracker {
+        Tracker { config, tracks: vec![], next_id: 1 }
+    }
+
+    pub fn config(&self) -> &TrackerConfig {
+        &self.config
+    }
+
+    /// Drops every track, e.g. on a scene cut. Ids keep increasing.
+    pub fn reset(&mut self) {
+        self.tracks.clear();
+    }
+
+    /// Feeds the detections of the next frame and returns the confirmed tracks matched in it.
+    pub fn update(&mut self, detections: &DetectionResult) -> MOTResult {
+        let config = self.config;
+        for track in &mut self.tracks {
+            track.kalman.predict();
+        }
+        let (high, low): (Vec<usize>, Vec<usize>) = (0..detections.scores.len())
+            .filter(|&i| detections.scores[i] >= config.low_score_threshold)
+            .partition(|&i| detections.scores[i] >= config.high_score_threshold);
+
+        let mut matched_tracks = vec![false; self.tracks.len()];
+        let confirmed: Vec<usize> = (0..self.tracks.len()).filter(|&t| self.tracks[t].confirmed()).collect();
+        // the first round weights the overlap by the detection score
+        let unmatched_high = self.associate(detections, &confirmed, &high, config.match_iou_threshold, true, &mut matched_tracks);
+        let still_tracked: Vec<usize> = confirmed.iter().copied()
+            .filter(|&t| !matched_tracks[t] && self.tracks[t].lost_frames == 0)
+            .collect();
+        self.associate(detections, &still_tracked, &low, config.low_match_iou_threshold, false, &mut matched_tracks);
+        let unconfirmed: Vec<usize> = (0..self.tracks.len()).filter(|&t| !self.tracks[t].confirmed()).collect();
+        let new_detections = self.associate(detections, &unconfirmed, &unmatched_high, config.match_iou_threshold, true, &mut matched_tracks);
+
+        let mut result = MOTResult::default();
+        let mut kept = Vec::with_capacity(self.tracks.len());
+        for (mut track, matched) in self.tracks.drain(..).zip(matched_tracks) {
+            if matched {
+                track.hits += 1;
+                track.lost_frames = 0;
+                if track.id.is_none() && track.hits >= config.confirm_frames {
+                    track.id = Some(self.next_id);
+                    self.next_id += 1;
+                }
+                if let Some(id) = track.id {
+                    result.boxes.push(track.kalman.xyxy().to_vec());
+                    result.ids.push(id);
+                    result.scores.push(track.score);
+                    result.class_ids.push(track.label_id);
+                }
+            } else {
+                // an unconfirmed track has to match in consecutive frames
+                if !track.confirmed() {
+                    continue;
+                }
+                track.lost_frames += 1;
+                if track.lost_frames > config.max_lost_frames {
+                    continue;
+                }
+            }
+            kept.push(track);
+        }
+        self.tracks = kept;
+
+        for i in new_detections {
+            if detections.scores[i] < config.new_track_threshold {
+                continue;
+            }
+            let mut track = Track {
+                id: None,
+                label_id: detections.label_ids[i],
+                score: detections.scores[i],
+                kalman: KalmanBoxFilter::new(&detections.boxes[i]),
+                hits: 1,
+                lost_frames: 0,
+            };
+            if config.confirm_frames <= 1 {
+                track.id = Some(self.next_id);
+                self.next_id += 1;
+                result.boxes.push(detections.boxes[i].clone());
+                result.ids.push(self.next_id - 1);
+                result.scores.push(track.score);
+                result.class_ids.push(track.label_id);
+            }
+            self.tracks.push(track);
+        }
+        result
+    }
+
+    /// Greedily matches `tracks` and `dets` by descending similarity, updates the matched tracks
+    /// and returns the unmatched detections.
+    fn associate(&mut self, detections: &DetectionResult, tracks: &[usize], dets: &[usize], iou_threshold: f32,
+                 weight_by_score: bool, matched_tracks: &mut [bool]) -> Vec<usize> {
+        let mut pairs = vec![];
+        for &t in tracks {
+            let track = &self.tracks[t];
+            let predicted = track.kalman.xyxy();
+            for &d in dets {
+                if self.config.per_class && detections.label_ids[d] != track.label_id {
+                    continue;
+                }
+                let overlap = iou(&predicted, &detections.boxes[d]);
+                if overlap < iou_threshold {
+                    continue;
+                }
+                let similarity = if weight_by_score { overlap * detections.scores[d] } else { overlap };
+                pairs.push((similarity, t, d));
+            }
+        }
+        pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut matched_dets = vec![];
+        for (_, t, d) in pairs {
+            if matched_tracks[t] || matched_dets.contains(&d) {
+                continue;
+            }
+            matched_tracks[t] = true;
+            matched_dets.push(d);
+            let track = &mut self.tracks[t];
+            track.kalman.update(&detections.boxes[d]);
+            track.score = detections.scores[d];
+            track.label_id = detections.label_ids[d];
+        }
+        dets.iter().copied().filter(|d| !matched_dets.contains(d)).collect()
+    }
+}
+
+const STD_WEIGHT_POSITION: f32 = 1.0 / 20.0;
+const STD_WEIGHT_VELOCITY: f32 = 1.0 / 160.0;
+
+/// Constant velocity Kalman filter over (center x, center y, aspect ratio, height) and their
+/// velocities, with the noise model of ByteTrack/DeepSORT.
+struct KalmanBoxFilter {
+    mean: [f32; 8],
+    covariance: [[f32; 8]; 8],
+}
+
+// indexed loops read closer to the matrix formulas
+#[allow(clippy::needless_range_loop)]
+impl KalmanBoxFilter {
+    fn new(xyxy: &[f32]) -> KalmanBoxFilter {
+        let m = to_xyah(xyxy);
+        let h = m[3];
+        let std = [
+            2.0 * STD_WEIGHT_POSITION * h, 2.0 * STD_WEIGHT_POSITION * h, 1e-2, 2.0 * STD_WEIGHT_POSITION * h,
+            10.0 * STD_WEIGHT_VELOCITY * h, 10.0 * STD_WEIGHT_VELOCITY * h, 1e-5, 10.0 * STD_WEIGHT_VELOCITY * h,
+        ];
+        let mut covariance = [[0.0; 8]; 8];
+        for i in 0..8 {
+            covariance[i][i] = std[i] * std[i];
+        }
+        KalmanBoxFilter { mean: [m[0], m[1], m[2], m[3], 0.0, 0.0, 0.0, 0.0], covariance }
+    }
+
+    fn predict(&mut self) {
+        let h = self.mean[3];
+        let std = [
+            STD_WEIGHT_POSITION * h, STD_WEIGHT_POSITION * h, 1e-2, STD_WEIGHT_POSITION * h,
+            STD_WEIGHT_VELOCITY * h, STD_WEIGHT_VELOCITY * h, 1e-5, STD_WEIGHT_VELOCITY * h,
+        ];
+        for i in 0..4 {
+            self.mean[i] += self.mean[i + 4];
+        }
+        // P = F P F^T + Q with F = [[I, I], [0, I]]
+        let p = self.covariance;
+        let mut fp = p;
+        for i in 0..4 {
+            for j in 0..8 {
+                fp[i][j] = p[i][j] + p[i + 4][j];
+            }
+        }
+        let mut fpf = fp;
+        for i in 0..8 {
+            for j in 0..4 {
+                fpf[i][j] = fp[i][j] + fp[i][j + 4];
+            }
+        }
+        for i in 0..8 {
+            fpf[i][i] += std[i] * std[i];
+        }
+        self.covariance = fpf;
+    }
+
+    fn update(&mut self, xyxy: &[f32]) {
+        let z = to_xyah(xyxy);
+        let h = self.mean[3];
+        let std = [STD_WEIGHT_POSITION * h, STD_WEIGHT_POSITION * h, 1e-1, STD_WEIGHT_POSITION * h];
+        // S = H P H^T + R, the top left block of P
+        let mut s = [[0.0; 4]; 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                s[i][j] = self.covariance[i][j];
+            }
+            s[i][i] += std[i] * std[i];
+        }
+        let s_inv = match invert4(s) {
+            Some(s_inv) => s_inv,
+            None => return,
+        };
+        // K = P H^T S^-1
+        let mut gain = [[0.0; 4]; 8];
+        for i in 0..8 {
+            for j in 0..4 {
+                gain[i][j] = (0..4).map(|k| self.covariance[i][k] * s_inv[k][j]).sum();
+            }
+        }
+        let innovation: Vec<f32> = (0..4).map(|i| z[i] - self.mean[i]).collect();
+        for i in 0..8 {
+            self.mean[i] += (0..4).map(|j| gain[i][j] * innovation[j]).sum::<f32>();
+        }
+        // P = P - K H P
+        let p = self.covariance;
+        for i in 0..8 {
+            for j in 0..8 {
+                self.covariance[i][j] = p[i][j] - (0..4).map(|k| gain[i][k] * p[k][j]).sum::<f32>();
+            }
+        }
+    }
+
+    fn xyxy(&self) -> [f32; 4] {
+        let [cx, cy, a, h] = [self.mean[0], self.mean[1], self.mean[2], self.mean[3]];
+        let w = a * h;
+        [cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0]
+    }
+}
+
+fn to_xyah(xyxy: &[f32]) -> [f32; 4] {
+    let w = xyxy[2] - xyxy[0];
+    let h = (xyxy[3] - xyxy[1]).max(f32::EPSILON);
+    [xyxy[0] + w / 2.0, xyxy[1] + h / 2.0, w / h, h]
+}
+
+/// Gauss-Jordan inverse with partial pivoting.
+#[allow(clippy::needless_range_loop)]
+fn invert4(m: [[f32; 4]; 4]) -> Option<[[f32; 4]; 4]> {
+    let mut a = m;
+    let mut inv = [[0.0; 4]; 4];
+    for i in 0..4 {
+        inv[i][i] = 1.0;
+    }
+    for col in 0..4 {
+        let pivot = (col..4).max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
+        let d = a[col][col];
+        for j in 0..4 {
+            a[col][j] /= d;
+            inv[col][j] /= d;
+        }
+        for row in 0..4 {
+            if row != col {
+                let f = a[row][col];
+                for j in 0..4 {
+                    a[row][j] -= f * a[col][j];
+                    inv[row][j] -= f * inv[col][j];
+                }
+            }
+        }
+    }
+    Some(inv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detections(objects: &[([f32; 4], f32, i32)]) -> DetectionResult {
+        DetectionResult {
+            boxes: objects.iter().map(|(b, _, _)| b.to_vec()).collect(),
+            scores: objects.iter().map(|(_, s, _)| *s).collect(),
+            label_ids: objects.iter().map(|(_, _, l)| *l).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn shifted(b: [f32; 4], dx: f32) -> [f32; 4] {
+        [b[0] + dx, b[1], b[2] + dx, b[3]]
+    }
+
+    const A: [f32; 4] = [10.0, 10.0, 50.0, 90.0];
+    const B: [f32; 4] = [200.0, 20.0, 240.0, 100.0];
+
+    #[test]
+    fn ids_persist_across_frames() {
+        let mut tracker = Tracker::new(TrackerConfig { confirm_frames: 1, ..Default::default() });
+        let first = tracker.update(&detections(&[(A, 0.9, 0), (B, 0.8, 0)]));
+        assert_eq!(first.ids, vec![1, 2]);
+        for frame in 1..10 {
+            let dx = frame as f32 * 2.0;
+            // listed in the other order, the ids follow the boxes
+            let result = tracker.update(&detections(&[(shifted(B, dx), 0.8, 0), (shifted(A, dx), 0.9, 0)]));
+            assert_eq!(result.ids, vec![1, 2]);
+            assert!((result.boxes[0][0] - (A[0] + dx)).abs() < 2.0);
+        }
+    }
+
+    #[test]
+    fn confirm_frames_gates_new_tracks() {
+        let mut tracker = Tracker::new(TrackerConfig { confirm_frames: 3, ..Default::default() });
+        assert!(tracker.update(&detections(&[(A, 0.9, 0)])).ids.is_empty());
+        assert!(tracker.update(&detections(&[(A, 0.9, 0)])).ids.is_empty());
+        assert_eq!(tracker.update(&detections(&[(A, 0.9, 0)])).ids, vec![1]);
+
+        // a missed frame drops an unconfirmed track, so counting starts over
+        tracker.update(&detections(&[(B, 0.9, 0)]));
+        tracker.update(&detections(&[]));
+        assert!(tracker.update(&detections(&[(B, 0.9, 0)])).ids.is_empty());
+        assert!(tracker.update(&detections(&[(B, 0.9, 0)])).ids.is_empty());
+        assert_eq!(tracker.update(&detections(&[(B, 0.9, 0)])).ids, vec![2]);
+    }
+
+    #[test]
+    fn lost_tracks_recover_their_id() {
+        let mut tracker = Tracker::new(TrackerConfig { confirm_frames: 1, max_lost_frames: 3, ..Default::default() });
+        assert_eq!(tracker.update(&detections(&[(A, 0.9, 0)])).ids, vec![1]);
+        for _ in 0..3 {
+            assert!(tracker.update(&detections(&[])).ids.is_empty());
+        }
+        assert_eq!(tracker.update(&detections(&[(A, 0.9, 0)])).ids, vec![1]);
+
+        // lost for longer than max_lost_frames, it comes back as a new track
+        for _ in 0..4 {
+            tracker.update(&detections(&[]));
+        }
+        assert_eq!(tracker.update(&detections(&[(A, 0.9, 0)])).ids, vec![2]);
+    }
+
+    #[test]
+    fn low_score_detections_only_keep_tracks_alive() {
+        let mut tracker = Tracker::new(TrackerConfig { confirm_frames: 1, ..Default::default() });
+        assert!(tracker.update(&detections(&[(A, 0.3, 0)])).ids.is_empty());
+        assert_eq!(tracker.update(&detections(&[(A, 0.9, 0)])).ids, vec![1]);
+        assert_eq!(tracker.update(&detections(&[(A, 0.3, 0)])).ids, vec![1]);
+    }
+
+    #[test]
+    fn per_class_only_matches_the_same_label() {
+        let mut tracker = Tracker::new(TrackerConfig { confirm_frames: 1, per_class: true, ..Default::default() });
+        assert_eq!(tracker.update(&detections(&[(A, 0.9, 0)])).ids, vec![1]);
+        let result = tracker.update(&detections(&[(A, 0.9, 1)]));
+        assert_eq!((result.ids, result.class_ids), (vec![2], vec![1]));
+
+        let mut tracker = Tracker::new(TrackerConfig { confirm_frames: 1, ..Default::default() });
+        tracker.update(&detections(&[(A, 0.9, 0)]));
+        let result = tracker.update(&detections(&[(A, 0.9, 1)]));
+        assert_eq!((result.ids, result.class_ids), (vec![1], vec![1]));
+    }
+
+    #[test]
+    fn reset_drops_tracks_but_not_ids() {
+        let mut tracker = Tracker::new(TrackerConfig { confirm_frames: 1, ..Default::default() });
+        tracker.update(&detections(&[(A, 0.9, 0)]));
+        tracker.reset();
+        assert_eq!(tracker.update(&detections(&[(A, 0.9, 0)])).ids, vec![2]);
+    }
+}
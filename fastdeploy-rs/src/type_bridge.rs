@@ -642,6 +642,9 @@ impl Mat {
     }
 }
 
+// the image is owned by the Mat, so it can be moved to a worker thread
+unsafe impl Send for Mat {}
+
 impl Drop for Mat {
     fn drop(&mut self) {
         println!("drop single");
@@ -684,6 +687,21 @@ impl From<&mut Vec<Mat>> for OneDimMatWrapper {
     }
 }
 
+/// Borrowed images laid out as the contiguous `FD_C_Mat` array the batch apis expect.
+pub struct OneDimMatRefWrapper {
+    // owns the array `ptr` points into
+    _mats: Vec<FD_C_Mat>,
+    pub ptr: Box<FD_C_OneDimMat>,
+}
+
+impl From<&[&Mat]> for OneDimMatRefWrapper {
+    fn from(value: &[&Mat]) -> Self {
+        let mut mats: Vec<FD_C_Mat> = value.iter().map(|img| img.ptr).collect();
+        // the vec's buffer doesn't move with the wrapper, so `ptr` stays valid
+        let ptr = Box::new(FD_C_OneDimMat { size: mats.len(), data: mats.as_mut_ptr() });
+        Self { _mats: mats, ptr }
+    }
+}
 
 // impl Drop for OneDimMatWrapper {
 //     fn drop(&mut self) {
@@ -5,6 +5,7 @@ use fastdeploy_bind::FD_C_VisSegmentation;
 
 use crate::result::ClassifyResult;
 use crate::result::DetectionResult;
+use crate::result::MOTResult;
 use crate::result::OCRResult;
 use crate::result::SegmentationResult;
 use crate::type_bridge::common::vec_to_c_1_cstr;
@@ -81,4 +82,14 @@ pub mod segmentation {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub mod mot {
+    use super::*;
+
+    /// Draws the boxes labeled with their track ids.
+    pub fn vis_mot(img: &Mat, result: &MOTResult, score_threshold: f32, line_size: i32, font_size: f32) -> Mat {
+        let labels = result.ids.iter().map(|id| format!("id {}", id)).collect();
+        detection::vis_detection_with_label(img, result.to_detection_result(), labels, score_threshold, line_size, font_size)
+    }
+}